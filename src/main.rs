@@ -1,4 +1,5 @@
 use utils::logger::Logger;
 pub mod utils;
+#[cfg(feature = "window")]
 pub mod window;
 fn main() {}
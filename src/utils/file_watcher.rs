@@ -0,0 +1,108 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+/// Watch a file for changes by polling its modified time
+///
+/// Editors often write a file several times in a row when saving so a
+/// change is only reported once the file has been quiet for the debounce window
+pub struct FileWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    debounce: Duration,
+    pending: Option<Instant>,
+}
+impl FileWatcher {
+    pub fn new<P: AsRef<Path>>(path: P, debounce: Duration) -> Self {
+        let path = path.as_ref().to_path_buf();
+        Self {
+            modified: Self::modified_time(&path),
+            path,
+            debounce,
+            pending: None,
+        }
+    }
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    fn modified_time(path: &Path) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|meta| meta.modified()).ok()
+    }
+    /// Check the file for changes
+    ///
+    /// Returns `true` once per settled change
+    pub fn poll(&mut self) -> bool {
+        let modified = Self::modified_time(&self.path);
+        if modified != self.modified {
+            self.modified = modified;
+            // Restart the window on every write so a burst counts as one change
+            self.pending = Some(Instant::now());
+            return false;
+        }
+        match self.pending {
+            Some(changed) if changed.elapsed() >= self.debounce => {
+                self.pending = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod file_watcher_test {
+    use super::*;
+    use std::{fs::File, thread::sleep};
+
+    fn touch(path: &Path, modified: SystemTime) {
+        File::options()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_poll_unchanged() {
+        let path = std::env::temp_dir().join("stellar2d-watch-unchanged.txt");
+        touch(&path, SystemTime::now());
+
+        let mut watcher = FileWatcher::new(&path, Duration::ZERO);
+        assert!(!watcher.poll());
+        assert!(!watcher.poll());
+    }
+    #[test]
+    fn test_poll_touched() {
+        let path = std::env::temp_dir().join("stellar2d-watch-touched.txt");
+        let now = SystemTime::now();
+        touch(&path, now);
+
+        let mut watcher = FileWatcher::new(&path, Duration::ZERO);
+        touch(&path, now + Duration::from_secs(1));
+        // First poll sees the write, the next reports it once settled
+        assert!(!watcher.poll());
+        assert!(watcher.poll());
+        assert!(!watcher.poll());
+    }
+    #[test]
+    fn test_poll_debounces_writes() {
+        let path = std::env::temp_dir().join("stellar2d-watch-debounce.txt");
+        let now = SystemTime::now();
+        touch(&path, now);
+
+        let mut watcher = FileWatcher::new(&path, Duration::from_millis(50));
+        for second in 1..=3 {
+            touch(&path, now + Duration::from_secs(second));
+            assert!(!watcher.poll());
+        }
+        assert!(!watcher.poll());
+        sleep(Duration::from_millis(60));
+        assert!(watcher.poll());
+        assert!(!watcher.poll());
+    }
+}
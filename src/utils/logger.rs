@@ -1,8 +1,196 @@
-use std::io::{stdout, Write};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    io::{self, stdout, IsTerminal, Stdout, Write},
+    panic,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
-use super::time;
+use super::time::DateTime;
+#[cfg(feature = "window")]
+use windows::{
+    core::PCSTR,
+    Win32::System::{
+        Console::{
+            GetConsoleMode, GetStdHandle, SetConsoleMode, CONSOLE_MODE,
+            ENABLE_VIRTUAL_TERMINAL_PROCESSING, STD_ERROR_HANDLE, STD_OUTPUT_HANDLE,
+        },
+        Diagnostics::Debug::OutputDebugStringA,
+    },
+};
+/// Timestamp precision used in each log line
+#[derive(Debug, Default, Clone, Copy)]
+pub enum TimeFormat {
+    /// `2024-6-17 12:30:45`
+    Seconds,
+    /// `2024-6-17 12:30:45.123`
+    #[default]
+    Millis,
+    /// Format the timestamp with a user supplied function
+    Custom(fn(&DateTime) -> String),
+}
+impl TimeFormat {
+    fn format(&self, now: &DateTime) -> String {
+        match self {
+            TimeFormat::Seconds => format!(
+                "{}-{}-{} {}:{}:{}",
+                now.year, now.month, now.day, now.hour, now.minute, now.second
+            ),
+            TimeFormat::Millis => format!(
+                "{}-{}-{} {}:{}:{}.{}",
+                now.year,
+                now.month,
+                now.day,
+                now.hour,
+                now.minute,
+                now.second,
+                now.micros / 1000
+            ),
+            TimeFormat::Custom(format) => format(now),
+        }
+    }
+}
+/// Layout of each log line
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// `[LEVEL] time: msg key=value`
+    #[default]
+    Text,
+    /// `{"level":"LEVEL","ts":"time","msg":"msg","key":"value"}`
+    Json,
+}
+// Escape a string to be embedded in a JSON string literal
+fn escape_json(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+/// Output that discards every write
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NullWriter;
+impl Write for NullWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+/// Output that forwards each line to an attached debugger with `OutputDebugStringA`
+///
+/// Partial lines are held until a newline or `flush`
+#[cfg(feature = "window")]
+#[derive(Debug, Default)]
+pub struct DebugOutputWriter {
+    line: Vec<u8>,
+}
+#[cfg(feature = "window")]
+impl DebugOutputWriter {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    fn send_line(&mut self) {
+        // Interior nuls would cut the line short
+        self.line.retain(|&byte| byte != 0);
+        self.line.push(0);
+        unsafe { OutputDebugStringA(PCSTR(self.line.as_ptr())) };
+        self.line.clear();
+    }
+}
+#[cfg(feature = "window")]
+impl Write for DebugOutputWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for &byte in buf {
+            self.line.push(byte);
+            if byte == b'\n' {
+                self.send_line();
+            }
+        }
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.line.is_empty() {
+            self.send_line();
+        }
+        Ok(())
+    }
+}
+// Turn on escape code processing for the console behind stdout and stderr
+//
+// Classic consoles print escape codes as text until this is enabled
+#[cfg(feature = "window")]
+fn enable_virtual_terminal() -> bool {
+    let mut enabled = false;
+    for std_handle in [STD_OUTPUT_HANDLE, STD_ERROR_HANDLE] {
+        unsafe {
+            let Ok(handle) = GetStdHandle(std_handle) else {
+                continue;
+            };
+            let mut mode = CONSOLE_MODE::default();
+            if GetConsoleMode(handle, &mut mode).is_ok()
+                && SetConsoleMode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING).is_ok()
+            {
+                enabled = true;
+            }
+        }
+    }
+    enabled
+}
+#[cfg(not(feature = "window"))]
+fn enable_virtual_terminal() -> bool {
+    true
+}
+/// Logger for the current build profile
+///
+/// Debug builds log to `stdout` while release builds swap in a
+/// disabled `NullWriter` logger
+#[cfg(debug_assertions)]
+pub type BuildLogger = Logger<Stdout>;
+#[cfg(not(debug_assertions))]
+pub type BuildLogger = Logger<NullWriter>;
+/// Create the logger for the current build profile
+///
+/// Release builds ignore the `threshold` and log nothing
+pub fn build_logger(threshold: usize) -> BuildLogger {
+    #[cfg(debug_assertions)]
+    return Logger::new(stdout(), threshold);
+    #[cfg(not(debug_assertions))]
+    return Logger::disabled();
+}
+// Tracks the last log to detect consecutive repeats
+#[derive(Debug)]
+struct RateLimit {
+    window: Duration,
+    last_hash: u64,
+    last_seen: Instant,
+    repeated: usize,
+    level: &'static str,
+}
+/// Logger shared across threads
+pub type SharedLogger<T> = Arc<Mutex<Logger<T>>>;
+// Highest level compiled in, set with the `max_level_*` features
+const MAX_LEVEL: usize = if cfg!(feature = "max_level_error") {
+    1
+} else if cfg!(feature = "max_level_warn") {
+    2
+} else {
+    3
+};
 /// Logger threshold levels
 ///
+/// Off - 0
+///
 /// Error - 1
 ///
 /// Warn - 2
@@ -11,72 +199,330 @@ use super::time;
 ///
 /// The `threshold` will include all levels less than or equal to
 /// the `threshold`
+///
+/// The `max_level_error`, `max_level_warn`, and `max_level_info` features
+/// compile out every level above them; a runtime `threshold` above the
+/// max level has no effect on those levels
 #[derive(Debug, Default)]
 pub struct Logger<T: Write> {
     output: T,
     threshold: usize,
+    time_format: TimeFormat,
+    rate_limit: Option<RateLimit>,
+    auto_flush: bool,
+    format: LogFormat,
+    color: bool,
+    // Emitted errors, warnings, and infos
+    counts: (usize, usize, usize),
+    // The last log was written without a newline
+    open_line: bool,
 }
 impl<T: Write> Logger<T> {
     pub fn new(output: T, threshold: usize) -> Self {
-        Self { output, threshold }
+        Self {
+            output,
+            threshold,
+            time_format: Default::default(),
+            rate_limit: None,
+            auto_flush: false,
+            format: Default::default(),
+            color: false,
+            counts: (0, 0, 0),
+            open_line: false,
+        }
+    }
+    /// Set the timestamp precision of each log
+    ///
+    /// Defaults to `TimeFormat::Millis`
+    pub fn with_time_format(mut self, time_format: TimeFormat) -> Self {
+        self.time_format = time_format;
+        self
+    }
+    /// Set the layout of each log line
+    ///
+    /// Defaults to `LogFormat::Text`
+    pub fn with_format(mut self, format: LogFormat) -> Self {
+        self.format = format;
+        self
+    }
+    /// Suppress identical consecutive logs seen within `window` of each other
+    ///
+    /// A `(repeated N times)` line is logged once a different log ends the burst
+    /// or on `flush`
+    pub fn rate_limited(mut self, window: Duration) -> Self {
+        self.rate_limit = Some(RateLimit {
+            window,
+            last_hash: 0,
+            last_seen: Instant::now(),
+            repeated: 0,
+            level: "",
+        });
+        self
+    }
+    /// Flush the output after every log with a newline
+    ///
+    /// Off by default, useful to keep the last logs before a crash
+    pub fn with_auto_flush(mut self, auto_flush: bool) -> Self {
+        self.auto_flush = auto_flush;
+        self
+    }
+    /// Change the threshold level while running
+    ///
+    /// A `SharedLogger` can be changed through its lock
+    pub fn set_threshold(&mut self, threshold: usize) {
+        self.threshold = threshold;
+    }
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+    /// Number of errors, warnings, and infos written to the output
+    ///
+    /// Logs below the threshold or suppressed by rate limiting are not counted
+    pub fn counts(&self) -> (usize, usize, usize) {
+        self.counts
+    }
+    /// A session summary such as `12 errors, 3 warnings logged`
+    pub fn summary(&self) -> String {
+        format!(
+            "{} errors, {} warnings logged",
+            self.counts.0, self.counts.1
+        )
+    }
+    /// Flush any buffered logs to the output
+    ///
+    /// A pending `(repeated N times)` line is written first
+    pub fn flush(&mut self) {
+        self.write_repeated();
+        match self.output.flush() {
+            Err(x) => eprintln!("{}", x),
+            _ => (),
+        }
+    }
+    fn timestamp(&self) -> String {
+        self.time_format.format(&DateTime::now_utc())
+    }
+    // Wrap a line in the ANSI color of its level
+    fn colorize(&self, level: &str, line: String) -> String {
+        if !self.color {
+            return line;
+        }
+        match level {
+            "ERROR" => format!("\x1b[31m{}\x1b[0m", line),
+            "WARNING" => format!("\x1b[33m{}\x1b[0m", line),
+            _ => line,
+        }
+    }
+    fn format_line(&self, level: &str, msg: &str, fields: &[(&str, &str)]) -> String {
+        match self.format {
+            LogFormat::Text => {
+                let mut line = format!("[{}] {}: {}", level, self.timestamp(), msg);
+                for (key, value) in fields {
+                    line.push_str(&format!(" {}={}", key, value));
+                }
+                line
+            }
+            LogFormat::Json => {
+                let mut line = format!(
+                    "{{\"level\":\"{}\",\"ts\":\"{}\",\"msg\":\"{}\"",
+                    level,
+                    escape_json(&self.timestamp()),
+                    escape_json(msg)
+                );
+                for (key, value) in fields {
+                    line.push_str(&format!(
+                        ",\"{}\":\"{}\"",
+                        escape_json(key),
+                        escape_json(value)
+                    ));
+                }
+                line.push('}');
+                line
+            }
+        }
+    }
+    // Write the `(repeated N times)` line of the current burst
+    fn write_repeated(&mut self) {
+        let Some(rate_limit) = self.rate_limit.as_mut() else {
+            return;
+        };
+        let (repeated, level) = (rate_limit.repeated, rate_limit.level);
+        if repeated == 0 {
+            return;
+        }
+        rate_limit.repeated = 0;
+        let summary = self.colorize(
+            level,
+            self.format_line(
+                level,
+                format!("(repeated {} times)", repeated).as_str(),
+                &[],
+            ),
+        );
+        // Keep the summary off a line left open by `log`, `wlog`, or `elog`
+        let prefix = if self.open_line { "\n" } else { "" };
+        self.open_line = false;
+        match writeln!(self.output, "{}{}", prefix, summary) {
+            Err(x) => eprintln!("{}", x),
+            _ => (),
+        }
+    }
+    // Check if the log repeats the previous one and summarize a finished burst
+    fn is_repeated(&mut self, level: &'static str, msg: &str) -> bool {
+        let Some(rate_limit) = self.rate_limit.as_mut() else {
+            return false;
+        };
+        let mut hasher = DefaultHasher::new();
+        (level, msg).hash(&mut hasher);
+        let hash = hasher.finish();
+        let now = Instant::now();
+
+        if hash == rate_limit.last_hash
+            && now.duration_since(rate_limit.last_seen) < rate_limit.window
+        {
+            rate_limit.repeated += 1;
+            rate_limit.last_seen = now;
+            return true;
+        }
+
+        self.write_repeated();
+        if let Some(rate_limit) = self.rate_limit.as_mut() {
+            rate_limit.last_hash = hash;
+            rate_limit.last_seen = now;
+            rate_limit.level = level;
+        }
+        false
+    }
+    fn write_log(
+        &mut self,
+        level: &'static str,
+        msg: &str,
+        fields: &[(&str, &str)],
+        newline: bool,
+    ) {
+        if self.is_repeated(level, msg) {
+            return;
+        }
+        match level {
+            "ERROR" => self.counts.0 += 1,
+            "WARNING" => self.counts.1 += 1,
+            _ => self.counts.2 += 1,
+        }
+        let line = self.colorize(level, self.format_line(level, msg, fields));
+        let result = if newline {
+            writeln!(self.output, "{}", line)
+        } else {
+            write!(self.output, "{}", line)
+        };
+        match result {
+            Err(x) => eprintln!("{}", x),
+            _ => (),
+        }
+        self.open_line = !newline;
+        if newline && self.auto_flush {
+            self.flush();
+        }
     }
     /// Info log with a newline '/n'
     pub fn logln(&mut self, msg: &str) {
-        if self.threshold == 3 {
-            match writeln!(self.output, "[INFO] {}: {}", time::now_utc(), msg) {
-                Err(x) => eprintln!("{}", x),
-                _ => (),
-            }
+        if MAX_LEVEL >= 3 && self.threshold == 3 {
+            self.write_log("INFO", msg, &[], true);
+        }
+    }
+    /// Info log with key-value fields and a newline '/n'
+    pub fn logln_kv(&mut self, msg: &str, fields: &[(&str, &str)]) {
+        if MAX_LEVEL >= 3 && self.threshold == 3 {
+            self.write_log("INFO", msg, fields, true);
         }
     }
     pub fn log(&mut self, msg: &str) {
-        if self.threshold == 3 {
-            match write!(self.output, "[INFO] {}: {}", time::now_utc(), msg) {
-                Err(x) => eprintln!("{}", x),
-                _ => (),
-            }
+        if MAX_LEVEL >= 3 && self.threshold == 3 {
+            self.write_log("INFO", msg, &[], false);
         }
     }
     /// Warning log with a newline '/n'
     pub fn wlogln(&mut self, msg: &str) {
-        if self.threshold >= 2 {
-            match writeln!(self.output, "[WARNING] {}: {}", time::now_utc(), msg) {
-                Err(x) => eprintln!("{}", x),
-                _ => (),
-            }
+        if MAX_LEVEL >= 2 && self.threshold >= 2 {
+            self.write_log("WARNING", msg, &[], true);
+        }
+    }
+    /// Warning log with key-value fields and a newline '/n'
+    pub fn wlogln_kv(&mut self, msg: &str, fields: &[(&str, &str)]) {
+        if MAX_LEVEL >= 2 && self.threshold >= 2 {
+            self.write_log("WARNING", msg, fields, true);
         }
     }
     pub fn wlog(&mut self, msg: &str) {
-        if self.threshold >= 2 {
-            match write!(self.output, "[WARNING] {}: {}", time::now_utc(), msg) {
-                Err(x) => eprintln!("{}", x),
-                _ => (),
-            }
+        if MAX_LEVEL >= 2 && self.threshold >= 2 {
+            self.write_log("WARNING", msg, &[], false);
         }
     }
     /// Error log with a newline '/n'
     pub fn elogln(&mut self, msg: &str) {
         if self.threshold >= 1 {
-            match writeln!(self.output, "[ERROR] {}: {}", time::now_utc(), msg) {
-                Err(x) => eprintln!("{}", x),
-                _ => (),
-            }
+            self.write_log("ERROR", msg, &[], true);
+        }
+    }
+    /// Error log with key-value fields and a newline '/n'
+    pub fn elogln_kv(&mut self, msg: &str, fields: &[(&str, &str)]) {
+        if self.threshold >= 1 {
+            self.write_log("ERROR", msg, fields, true);
         }
     }
     pub fn elog(&mut self, msg: &str) {
         if self.threshold >= 1 {
-            match write!(self.output, "[ERROR] {}: {}", time::now_utc(), msg) {
-                Err(x) => eprintln!("{}", x),
-                _ => (),
-            }
+            self.write_log("ERROR", msg, &[], false);
         }
     }
 }
+impl<T: Write + IsTerminal> Logger<T> {
+    /// Color errors red and warnings yellow
+    ///
+    /// Only enabled when the output is a terminal so redirected
+    /// output does not get escape codes
+    ///
+    /// On Windows the console must also accept escape codes
+    pub fn with_color(mut self) -> Self {
+        self.color = self.output.is_terminal() && enable_virtual_terminal();
+        self
+    }
+}
+impl<T: Write + Send + 'static> Logger<T> {
+    /// Log every panic as an error before running the default panic hook
+    pub fn install_panic_hook(logger: SharedLogger<T>) {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let payload = if let Some(msg) = info.payload().downcast_ref::<&str>() {
+                msg.to_string()
+            } else if let Some(msg) = info.payload().downcast_ref::<String>() {
+                msg.clone()
+            } else {
+                String::from("Box<dyn Any>")
+            };
+            let location = info
+                .location()
+                .map(|location| location.to_string())
+                .unwrap_or_default();
+            // A panic while the logger is held would deadlock on `lock`
+            if let Ok(mut logger) = logger.try_lock() {
+                logger.elogln(format!("panicked at {}: {}", location, payload).as_str());
+                logger.flush();
+            }
+            default_hook(info);
+        }));
+    }
+}
+impl Logger<NullWriter> {
+    /// Logger with every level suppressed
+    pub fn disabled() -> Self {
+        Self::new(NullWriter, 0)
+    }
+}
 
 #[cfg(test)]
 mod logger_log_test {
     use super::*;
     use regex::Regex;
+    use std::collections::HashMap;
     #[test]
     fn test_log() {
         let mut buffer = Vec::new();
@@ -114,4 +560,319 @@ mod logger_log_test {
 
         assert!(String::from_utf8(buffer).unwrap().starts_with("[ERROR]"))
     }
+    #[test]
+    fn test_threshold_off() {
+        let mut buffer = Vec::new();
+        let mut logger = Logger::new(&mut buffer, 0);
+        logger.log("Test message");
+        logger.logln("Test message");
+        logger.wlog("Test message");
+        logger.wlogln("Test message");
+        logger.elog("Test message");
+        logger.elogln("Test message");
+
+        assert!(buffer.is_empty())
+    }
+    #[cfg(feature = "window")]
+    #[test]
+    fn test_debug_output_writer() {
+        let mut writer = DebugOutputWriter::new();
+        // No debugger needs to be attached
+        assert_eq!(writer.write(b"first\nsec").unwrap(), 10);
+        assert_eq!(writer.line, b"sec");
+        assert!(writer.write_all(b"ond\0line").is_ok());
+        assert!(writer.flush().is_ok());
+        assert!(writer.line.is_empty());
+
+        let mut logger = Logger::new(DebugOutputWriter::new(), 3);
+        logger.logln("Test message");
+        assert!(logger.output.line.is_empty());
+    }
+    #[test]
+    fn test_counts() {
+        let mut buffer = Vec::new();
+        let mut logger = Logger::new(&mut buffer, 2);
+        logger.elogln("Test message");
+        logger.elog("Test message");
+        logger.wlogln("Test message");
+        logger.logln("Suppressed message");
+        logger.set_threshold(3);
+        logger.logln("Test message");
+        logger.set_threshold(1);
+        logger.wlogln("Suppressed message");
+
+        assert_eq!(logger.counts(), (2, 1, 1));
+        assert_eq!(logger.summary(), "2 errors, 1 warnings logged");
+    }
+    #[test]
+    fn test_counts_rate_limited() {
+        let mut buffer = Vec::new();
+        let mut logger = Logger::new(&mut buffer, 1).rate_limited(Duration::from_secs(60));
+        logger.elogln("Test message");
+        logger.elogln("Test message");
+        logger.elogln("Test message");
+
+        assert_eq!(logger.counts(), (1, 0, 0));
+    }
+    #[test]
+    fn test_set_threshold() {
+        let mut buffer = Vec::new();
+        let mut logger = Logger::new(&mut buffer, 1);
+        logger.logln("Suppressed message");
+        logger.set_threshold(3);
+        logger.logln("Test message");
+
+        assert_eq!(logger.threshold(), 3);
+        let output = String::from_utf8_lossy(&buffer);
+        assert!(!output.contains("Suppressed message"));
+        assert!(output.starts_with("[INFO]"));
+        assert!(output.contains("Test message"));
+    }
+    #[test]
+    fn test_set_threshold_shared() {
+        let shared: SharedLogger<Vec<u8>> = Arc::new(Mutex::new(Logger::new(Vec::new(), 0)));
+        shared.lock().unwrap().logln("Suppressed message");
+        shared.lock().unwrap().set_threshold(3);
+        shared.lock().unwrap().logln("Test message");
+
+        let logger = shared.lock().unwrap();
+        let output = String::from_utf8_lossy(&logger.output);
+        assert!(!output.contains("Suppressed message"));
+        assert!(output.starts_with("[INFO]"));
+        assert!(output.contains("Test message"));
+    }
+    #[test]
+    fn test_rate_limited() {
+        let mut buffer = Vec::new();
+        let mut logger = Logger::new(&mut buffer, 1).rate_limited(Duration::from_secs(60));
+        for _ in 0..100 {
+            logger.elogln("Test message");
+        }
+        logger.elogln("Other message");
+        let log = String::from_utf8(buffer).unwrap();
+
+        assert_eq!(log.matches("Test message").count(), 1);
+        assert_eq!(log.matches("(repeated 99 times)").count(), 1);
+        assert_eq!(log.lines().count(), 3);
+    }
+    #[test]
+    fn test_rate_limited_flush() {
+        let mut buffer = Vec::new();
+        let mut logger = Logger::new(&mut buffer, 1).rate_limited(Duration::from_secs(60));
+        for _ in 0..3 {
+            logger.elogln("Test message");
+        }
+        logger.flush();
+        logger.elogln("Test message");
+        logger.flush();
+        let log = String::from_utf8(buffer).unwrap();
+
+        assert_eq!(log.matches("(repeated 2 times)").count(), 1);
+        assert_eq!(log.matches("(repeated 1 times)").count(), 1);
+    }
+    #[test]
+    fn test_rate_limited_flush_open_line() {
+        let mut buffer = Vec::new();
+        let mut logger = Logger::new(&mut buffer, 1).rate_limited(Duration::from_secs(60));
+        for _ in 0..5 {
+            logger.elog("Test message");
+        }
+        logger.flush();
+        let log = String::from_utf8(buffer).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(": Test message"));
+        assert!(lines[1].ends_with(": (repeated 4 times)"));
+    }
+    #[test]
+    fn test_rate_limited_window_elapsed() {
+        let mut buffer = Vec::new();
+        let mut logger = Logger::new(&mut buffer, 1).rate_limited(Duration::ZERO);
+        logger.elogln("Test message");
+        logger.elogln("Test message");
+
+        assert_eq!(String::from_utf8(buffer).unwrap().lines().count(), 2);
+    }
+    #[test]
+    fn test_build_logger() {
+        let logger = build_logger(2);
+        if cfg!(debug_assertions) {
+            assert_eq!(logger.threshold(), 2);
+        } else {
+            assert_eq!(logger.threshold(), 0);
+        }
+    }
+    #[derive(Default)]
+    struct FlushCounter {
+        flushes: usize,
+    }
+    impl Write for FlushCounter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            self.flushes += 1;
+            Ok(())
+        }
+    }
+    #[test]
+    fn test_auto_flush() {
+        let mut counter = FlushCounter::default();
+        let mut logger = Logger::new(&mut counter, 3).with_auto_flush(true);
+        logger.logln("Test message");
+        logger.wlogln("Test message");
+        logger.elogln("Test message");
+        logger.elog("Test message");
+
+        assert_eq!(counter.flushes, 3);
+    }
+    #[test]
+    fn test_no_auto_flush() {
+        let mut counter = FlushCounter::default();
+        let mut logger = Logger::new(&mut counter, 3);
+        logger.logln("Test message");
+        logger.flush();
+
+        assert_eq!(counter.flushes, 1);
+    }
+    #[test]
+    fn test_panic_hook() {
+        // Restored afterwards so other tests keep the hook they started with
+        let previous = panic::take_hook();
+        let logger = Arc::new(Mutex::new(Logger::new(Vec::new(), 1)));
+        Logger::install_panic_hook(logger.clone());
+        let result = std::thread::spawn(|| panic!("Test panic")).join();
+        panic::set_hook(previous);
+        let log = String::from_utf8(logger.lock().unwrap().output.clone()).unwrap();
+
+        assert!(result.is_err());
+        assert!(log.starts_with("[ERROR]"));
+        assert!(log.contains("Test panic"));
+    }
+    // Parse a flat JSON object of string values
+    fn parse_json(json: &str) -> HashMap<String, String> {
+        assert!(json.starts_with('{') && json.ends_with('}'));
+        let pair = Regex::new(r#""((?:[^"\\]|\\.)*)":"((?:[^"\\]|\\.)*)""#).unwrap();
+        let unescape = |value: &str| {
+            let mut unescaped = String::new();
+            let mut chars = value.chars();
+            while let Some(c) = chars.next() {
+                if c != '\\' {
+                    unescaped.push(c);
+                    continue;
+                }
+                unescaped.push(match chars.next().unwrap() {
+                    'n' => '\n',
+                    'r' => '\r',
+                    't' => '\t',
+                    escaped => escaped,
+                });
+            }
+            unescaped
+        };
+        pair.captures_iter(json)
+            .map(|c| (unescape(&c[1]), unescape(&c[2])))
+            .collect()
+    }
+    #[test]
+    fn test_json_format() {
+        let mut buffer = Vec::new();
+        let mut logger = Logger::new(&mut buffer, 1).with_format(LogFormat::Json);
+        logger.elogln_kv(
+            "Tile \"grass\" failed\nto load",
+            &[("path", "C:\\tiles\\grass.bmp"), ("index", "4")],
+        );
+        let log = String::from_utf8(buffer).unwrap();
+        let map = parse_json(log.trim_end());
+
+        assert_eq!(log.lines().count(), 1);
+        assert_eq!(map["level"], "ERROR");
+        assert!(!map["ts"].is_empty());
+        assert_eq!(map["msg"], "Tile \"grass\" failed\nto load");
+        assert_eq!(map["path"], "C:\\tiles\\grass.bmp");
+        assert_eq!(map["index"], "4");
+    }
+    #[test]
+    fn test_text_format_fields() {
+        let mut buffer = Vec::new();
+        let mut logger = Logger::new(&mut buffer, 3);
+        logger.logln_kv("Test message", &[("index", "4")]);
+
+        assert!(String::from_utf8(buffer)
+            .unwrap()
+            .ends_with(": Test message index=4\n"));
+    }
+    #[test]
+    fn test_no_color_redirected() {
+        let path =
+            std::env::temp_dir().join(format!("stellar2d-no-color-{}.log", std::process::id()));
+        let mut logger = Logger::new(std::fs::File::create(&path).unwrap(), 2).with_color();
+        logger.elogln("Test message");
+        logger.wlogln("Test message");
+        // Close the file so it can be removed
+        drop(logger);
+        let log = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(!log.contains('\x1b'));
+    }
+    #[test]
+    fn test_color() {
+        let mut buffer = Vec::new();
+        let mut logger = Logger::new(&mut buffer, 3);
+        logger.color = true;
+        logger.elogln("Test message");
+        logger.wlogln("Test message");
+        logger.logln("Test message");
+        let log = String::from_utf8(buffer).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+
+        assert!(lines[0].starts_with("\x1b[31m[ERROR]") && lines[0].ends_with("\x1b[0m"));
+        assert!(lines[1].starts_with("\x1b[33m[WARNING]") && lines[1].ends_with("\x1b[0m"));
+        assert!(lines[2].starts_with("[INFO]"));
+    }
+    #[test]
+    #[cfg(feature = "max_level_error")]
+    fn test_max_level_error() {
+        let mut buffer = Vec::new();
+        let mut logger = Logger::new(&mut buffer, 3);
+        logger.logln("Test message");
+        logger.wlogln("Test message");
+        logger.elogln("Test message");
+        let log = String::from_utf8(buffer).unwrap();
+
+        assert_eq!(log.lines().count(), 1);
+        assert!(log.starts_with("[ERROR]"));
+    }
+    #[test]
+    #[cfg(feature = "max_level_warn")]
+    fn test_max_level_warn() {
+        let mut buffer = Vec::new();
+        let mut logger = Logger::new(&mut buffer, 3);
+        logger.logln("Test message");
+
+        assert!(buffer.is_empty());
+    }
+    #[test]
+    fn test_seconds_time_format() {
+        let mut buffer = Vec::new();
+        let mut logger = Logger::new(&mut buffer, 3).with_time_format(TimeFormat::Seconds);
+        logger.log("Test message");
+        let timestamp =
+            Regex::new(r"^\[INFO\] \d{4}-\d{1,2}-\d{1,2} \d{1,2}:\d{1,2}:\d{1,2}: Test message$")
+                .unwrap();
+
+        assert!(timestamp.is_match(&String::from_utf8(buffer).unwrap()));
+    }
+    #[test]
+    fn test_custom_time_format() {
+        let mut buffer = Vec::new();
+        let mut logger = Logger::new(&mut buffer, 3)
+            .with_time_format(TimeFormat::Custom(|now| format!("{:06}", now.micros)));
+        logger.log("Test message");
+        let timestamp = Regex::new(r"^\[INFO\] \d{6}: Test message$").unwrap();
+
+        assert!(timestamp.is_match(&String::from_utf8(buffer).unwrap()));
+    }
 }
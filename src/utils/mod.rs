@@ -1,2 +1,4 @@
+pub mod file_watcher;
 pub mod logger;
+pub mod recent_files;
 pub mod time;
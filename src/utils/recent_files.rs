@@ -0,0 +1,113 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// A capped list of recently opened files, most recent first
+///
+/// The list is stored in a config file with one path per line
+pub struct RecentFiles {
+    config: PathBuf,
+    files: Vec<PathBuf>,
+    capacity: usize,
+}
+impl RecentFiles {
+    /// Read the list from a config file
+    ///
+    /// Entries whose files no longer exist are dropped and a missing
+    /// config file gives an empty list
+    pub fn load<P: AsRef<Path>>(config: P, capacity: usize) -> Self {
+        let config = config.as_ref().to_path_buf();
+        let mut files: Vec<PathBuf> = Vec::new();
+        if let Ok(contents) = fs::read_to_string(&config) {
+            for line in contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+            {
+                let file = PathBuf::from(line);
+                if file.exists() && !files.contains(&file) {
+                    files.push(file);
+                }
+            }
+        }
+        files.truncate(capacity);
+        Self {
+            config,
+            files,
+            capacity,
+        }
+    }
+    /// Move a file to the front of the list
+    ///
+    /// The oldest entry is dropped once the list is full
+    pub fn push<P: AsRef<Path>>(&mut self, file: P) {
+        let file = file.as_ref().to_path_buf();
+        self.files.retain(|existing| *existing != file);
+        self.files.insert(0, file);
+        self.files.truncate(self.capacity);
+    }
+    pub fn list(&self) -> &[PathBuf] {
+        &self.files
+    }
+    /// Write the list back to its config file
+    pub fn save(&self) -> io::Result<()> {
+        let mut config = fs::File::create(&self.config)?;
+        for file in &self.files {
+            writeln!(config, "{}", file.display())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod recent_files_test {
+    use super::*;
+    use std::fs::File;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(name)
+    }
+
+    #[test]
+    fn test_push_dedup_reorder() {
+        let mut recent = RecentFiles::load(temp_path("stellar2d-recent-none.txt"), 3);
+        recent.push("a.scene");
+        recent.push("b.scene");
+        recent.push("a.scene");
+
+        assert_eq!(
+            recent.list(),
+            [PathBuf::from("a.scene"), PathBuf::from("b.scene")]
+        );
+    }
+    #[test]
+    fn test_push_capped() {
+        let mut recent = RecentFiles::load(temp_path("stellar2d-recent-none.txt"), 2);
+        recent.push("a.scene");
+        recent.push("b.scene");
+        recent.push("c.scene");
+
+        assert_eq!(
+            recent.list(),
+            [PathBuf::from("c.scene"), PathBuf::from("b.scene")]
+        );
+    }
+    #[test]
+    fn test_load_prunes_missing() {
+        let config = temp_path("stellar2d-recent-prune.txt");
+        let present = temp_path("stellar2d-recent-present.scene");
+        let missing = temp_path("stellar2d-recent-missing.scene");
+        File::create(&present).unwrap();
+        _ = fs::remove_file(&missing);
+
+        let mut recent = RecentFiles::load(&config, 5);
+        recent.push(&present);
+        recent.push(&missing);
+        recent.save().unwrap();
+
+        let recent = RecentFiles::load(&config, 5);
+        assert_eq!(recent.list(), [present]);
+    }
+}
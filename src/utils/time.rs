@@ -1,4 +1,4 @@
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 fn is_leap_year(year: u32) -> bool {
     year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
 }
@@ -25,53 +25,71 @@ fn days_in_month(year: u32, month: u32) -> u32 {
         _ => 30, // should not occur
     }
 }
-/// Get the current utc time
-pub(crate) fn now_utc() -> String {
-    let now = SystemTime::now();
-    let duration_since_epoch = now.duration_since(UNIX_EPOCH).expect("Time went backwards");
-
-    // Get the total number of seconds and milliseconds since the epoch
-    let total_seconds = duration_since_epoch.as_secs();
-    let millis = duration_since_epoch.subsec_millis();
-
-    // Convert the total number of seconds to the current date and time
-    let mut remaining_seconds = total_seconds;
-    let mut year = 1970;
-    while remaining_seconds
-        >= if is_leap_year(year) {
-            366 * 86400
-        } else {
-            365 * 86400
-        }
-    {
-        remaining_seconds -= if is_leap_year(year) {
-            366 * 86400
-        } else {
-            365 * 86400
-        };
-        year += 1;
+/// A UTC date and time broken down into its calendar fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateTime {
+    pub year: u32,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    pub micros: u32,
+}
+impl DateTime {
+    /// Get the current utc date and time
+    pub fn now_utc() -> Self {
+        let now = SystemTime::now();
+        let duration_since_epoch = now.duration_since(UNIX_EPOCH).expect("Time went backwards");
+        Self::from_epoch(duration_since_epoch)
     }
+    /// Convert a duration since the unix epoch to a date and time
+    fn from_epoch(duration_since_epoch: Duration) -> Self {
+        // Get the total number of seconds and microseconds since the epoch
+        let total_seconds = duration_since_epoch.as_secs();
+        let micros = duration_since_epoch.subsec_micros();
 
-    let mut month = 1;
-    while remaining_seconds >= (days_in_month(year, month) * 86400) as u64 {
-        remaining_seconds -= (days_in_month(year, month) * 86400) as u64;
-        month += 1;
-    }
+        // Convert the total number of seconds to the current date and time
+        let mut remaining_seconds = total_seconds;
+        let mut year = 1970;
+        while remaining_seconds
+            >= if is_leap_year(year) {
+                366 * 86400
+            } else {
+                365 * 86400
+            }
+        {
+            remaining_seconds -= if is_leap_year(year) {
+                366 * 86400
+            } else {
+                365 * 86400
+            };
+            year += 1;
+        }
 
-    let day = (remaining_seconds / 86400) as u32 + 1;
-    remaining_seconds %= 86400;
-    let hour = (remaining_seconds / 3600) as u32;
-    let minute = ((remaining_seconds % 3600) / 60) as u32;
-    let second = (remaining_seconds % 60) as u32;
+        let mut month = 1;
+        while remaining_seconds >= (days_in_month(year, month) * 86400) as u64 {
+            remaining_seconds -= (days_in_month(year, month) * 86400) as u64;
+            month += 1;
+        }
 
-    (year, month, day, hour, minute, second, millis);
+        let day = (remaining_seconds / 86400) as u32 + 1;
+        remaining_seconds %= 86400;
+        let hour = (remaining_seconds / 3600) as u32;
+        let minute = ((remaining_seconds % 3600) / 60) as u32;
+        let second = (remaining_seconds % 60) as u32;
 
-    format!(
-        "{}-{}-{} {}:{}:{}.{}",
-        year, month, day, hour, minute, second, millis
-    )
+        Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            micros,
+        }
+    }
 }
-
 #[cfg(test)]
 mod time_test {
     use super::*;
@@ -90,4 +108,21 @@ mod time_test {
         assert_eq!(days_in_month(2021, 4), 30);
         assert_eq!(days_in_month(2021, 12), 31);
     }
+    #[test]
+    fn test_from_epoch() {
+        let date = DateTime::from_epoch(Duration::from_micros(951_827_696_123_456));
+
+        assert_eq!(
+            date,
+            DateTime {
+                year: 2000,
+                month: 2,
+                day: 29,
+                hour: 12,
+                minute: 34,
+                second: 56,
+                micros: 123_456,
+            }
+        );
+    }
 }
@@ -0,0 +1,101 @@
+//! Copy bitmaps to and from the system clipboard
+use windows::{
+    core::Result,
+    Win32::{
+        Foundation::{HANDLE, HWND},
+        Graphics::Gdi::HBITMAP,
+        System::DataExchange::{
+            CloseClipboard, EmptyClipboard, GetClipboardData, IsClipboardFormatAvailable,
+            OpenClipboard, SetClipboardData,
+        },
+        UI::WindowsAndMessaging::{CopyImage, IMAGE_BITMAP, LR_DEFAULTCOLOR},
+    },
+};
+
+// Standard clipboard format for a `HBITMAP`
+const CF_BITMAP: u32 = 2;
+
+/// Place a bitmap on the clipboard, replacing its contents
+///
+/// The `owner` window becomes the clipboard owner, without one
+/// the system refuses to set the data
+///
+/// On success the clipboard owns the bitmap and the handle
+/// must not be deleted or selected into a device context afterwards
+///
+/// On failure the caller keeps ownership
+pub(crate) fn set_bitmap(owner: HWND, bitmap: HBITMAP) -> Result<()> {
+    unsafe {
+        OpenClipboard(owner)?;
+        let result = EmptyClipboard().and_then(|_| SetClipboardData(CF_BITMAP, HANDLE(bitmap.0)));
+        _ = CloseClipboard();
+        result.map(|_| ())
+    }
+}
+
+/// Copy the bitmap currently on the clipboard
+///
+/// The returned bitmap is owned by the caller and should be
+/// released with `DeleteObject`
+pub(crate) fn get_bitmap() -> Option<HBITMAP> {
+    unsafe {
+        IsClipboardFormatAvailable(CF_BITMAP).ok()?;
+        OpenClipboard(None).ok()?;
+        // The clipboard keeps its own handle so hand back a copy
+        let copy = GetClipboardData(CF_BITMAP)
+            .and_then(|handle| CopyImage(handle, IMAGE_BITMAP, 0, 0, LR_DEFAULTCOLOR));
+        _ = CloseClipboard();
+        copy.ok().map(|handle| HBITMAP(handle.0))
+    }
+}
+
+#[cfg(test)]
+mod clipboard_tests {
+    use super::*;
+    use crate::window::win::{instance::Instance, paint::bitmap_info};
+    use windows::{
+        core::s,
+        Win32::{
+            Graphics::Gdi::{CreateCompatibleBitmap, DeleteObject, GetDC, ReleaseDC},
+            UI::WindowsAndMessaging::{
+                CreateWindowExA, DestroyWindow, HWND_MESSAGE, WINDOW_EX_STYLE, WINDOW_STYLE,
+            },
+        },
+    };
+
+    #[test]
+    fn test_bitmap_round_trip() {
+        let (owner, bitmap) = unsafe {
+            // Message-only window to own the clipboard
+            let owner = CreateWindowExA(
+                WINDOW_EX_STYLE::default(),
+                s!("STATIC"),
+                s!("test-clipboard"),
+                WINDOW_STYLE::default(),
+                0,
+                0,
+                0,
+                0,
+                HWND_MESSAGE,
+                None,
+                Instance::this(),
+                None,
+            );
+            let screen = GetDC(None);
+            let bitmap = CreateCompatibleBitmap(screen, 5, 3);
+            ReleaseDC(None, screen);
+            (owner, bitmap)
+        };
+        assert_ne!(owner.0, 0);
+        set_bitmap(owner, bitmap).unwrap();
+
+        let copy = get_bitmap().unwrap();
+        assert_ne!(copy, bitmap);
+        let info = bitmap_info(copy).unwrap();
+        assert_eq!((info.bmWidth, info.bmHeight), (5, 3));
+        unsafe {
+            _ = DeleteObject(copy);
+            _ = DestroyWindow(owner);
+        }
+    }
+}
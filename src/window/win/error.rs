@@ -0,0 +1,10 @@
+//! Readable system errors for failure logs
+use windows::Win32::Foundation::GetLastError;
+
+/// Code and system message of the last error on this thread
+///
+/// Call right after the failing function as other calls may overwrite it
+pub(crate) fn last_error() -> (u32, String) {
+    let code = unsafe { GetLastError() };
+    (code.0, code.to_hresult().message().trim_end().to_string())
+}
@@ -0,0 +1,198 @@
+//! The `EventQueue` buffers window messages decoded by `wndproc` so they can be
+//! handled in the main loop instead of inside the callback
+use crate::utils::file_watcher::FileWatcher;
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// The client area needs to be repainted
+    Paint,
+    /// The client area changed to the new width and height
+    Resize { width: u32, height: u32 },
+    /// A key was pressed with its virtual key code
+    ///
+    /// `repeat` is set for auto-repeats while the key is held
+    KeyDown { key: u32, repeat: bool },
+    /// The window moved to a monitor with a different dots per inch
+    DpiChanged { dpi: u32 },
+    /// Files dragged from the shell were dropped on the window
+    FilesDropped(Vec<String>),
+    /// A watched tileset changed on disk and was loaded again
+    TilesetReloaded { path: PathBuf },
+    /// The window is being destroyed
+    Close,
+}
+
+impl Event {
+    /// Decode the `wparam` and `lparam` of a `WM_KEYDOWN` message
+    pub(crate) fn key_down(wparam: usize, lparam: isize) -> Self {
+        Event::KeyDown {
+            key: wparam as u32,
+            // Bit 30 holds the previous key state
+            repeat: lparam & (1 << 30) != 0,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct EventQueue {
+    events: VecDeque<Event>,
+}
+impl EventQueue {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    pub fn push(&mut self, event: Event) {
+        self.events.push_back(event);
+    }
+    /// Take the oldest event
+    pub fn poll(&mut self) -> Option<Event> {
+        self.events.pop_front()
+    }
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+thread_local! {
+    // Messages are dispatched on the thread that created the window
+    static EVENTS: RefCell<EventQueue> = RefCell::new(EventQueue::new());
+}
+/// Queue an event for the current thread
+pub(crate) fn push_event(event: Event) {
+    EVENTS.with(|events| events.borrow_mut().push(event));
+}
+/// Take the oldest event queued by the windows of the current thread
+///
+/// Drain it each frame after dispatching messages
+pub fn poll_event() -> Option<Event> {
+    EVENTS.with(|events| events.borrow_mut().poll())
+}
+/// Reload a watched tileset once a change to its file settles
+///
+/// `reload` is called with the watched path and `TilesetReloaded` is queued
+/// when it returns `true`, a failed reload waits for the next change
+///
+/// Call it each frame alongside `poll_event`
+pub fn poll_tileset<F: FnOnce(&Path) -> bool>(watcher: &mut FileWatcher, reload: F) -> bool {
+    if !watcher.poll() || !reload(watcher.path()) {
+        return false;
+    }
+    push_event(Event::TilesetReloaded {
+        path: watcher.path().to_path_buf(),
+    });
+    true
+}
+
+#[cfg(test)]
+mod event_queue_tests {
+    use super::*;
+    use std::{
+        fs::File,
+        time::{Duration, SystemTime},
+    };
+    #[test]
+    fn test_poll_in_order() {
+        let mut queue = EventQueue::new();
+        queue.push(Event::Paint);
+        queue.push(Event::KeyDown {
+            key: 65,
+            repeat: false,
+        });
+        queue.push(Event::Resize {
+            width: 800,
+            height: 600,
+        });
+
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.poll(), Some(Event::Paint));
+        assert_eq!(
+            queue.poll(),
+            Some(Event::KeyDown {
+                key: 65,
+                repeat: false
+            })
+        );
+        assert_eq!(
+            queue.poll(),
+            Some(Event::Resize {
+                width: 800,
+                height: 600
+            })
+        );
+        assert_eq!(queue.poll(), None);
+        assert!(queue.is_empty());
+    }
+    #[test]
+    fn test_key_down_repeat() {
+        // Repeat count of 1 with the previous key state bit
+        assert_eq!(
+            Event::key_down(0x26, 0x4000_0001),
+            Event::KeyDown {
+                key: 0x26,
+                repeat: true
+            }
+        );
+        assert_eq!(
+            Event::key_down(0x26, 0x0000_0001),
+            Event::KeyDown {
+                key: 0x26,
+                repeat: false
+            }
+        );
+    }
+    #[test]
+    fn test_poll_event_thread_queue() {
+        push_event(Event::Close);
+        push_event(Event::Paint);
+
+        assert_eq!(poll_event(), Some(Event::Close));
+        assert_eq!(poll_event(), Some(Event::Paint));
+        assert_eq!(poll_event(), None);
+    }
+    #[test]
+    fn test_poll_tileset_reloaded() {
+        let path = std::env::temp_dir().join("stellar2d-tileset-reload.bmp");
+        let file = File::create(&path).unwrap();
+        let mut watcher = FileWatcher::new(&path, Duration::ZERO);
+        file.set_modified(SystemTime::now() + Duration::from_secs(1))
+            .unwrap();
+
+        let mut reloads = 0;
+        let mut reload = |reloaded: &Path| {
+            assert_eq!(reloaded, path);
+            reloads += 1;
+            true
+        };
+        // The write is seen first and reported once settled
+        assert!(!poll_tileset(&mut watcher, &mut reload));
+        assert!(poll_tileset(&mut watcher, &mut reload));
+        assert_eq!(reloads, 1);
+        assert_eq!(
+            poll_event(),
+            Some(Event::TilesetReloaded { path: path.clone() })
+        );
+        assert_eq!(poll_event(), None);
+        _ = std::fs::remove_file(&path);
+    }
+    #[test]
+    fn test_poll_tileset_reload_failed() {
+        let path = std::env::temp_dir().join("stellar2d-tileset-failed.bmp");
+        let file = File::create(&path).unwrap();
+        let mut watcher = FileWatcher::new(&path, Duration::ZERO);
+        file.set_modified(SystemTime::now() + Duration::from_secs(1))
+            .unwrap();
+
+        assert!(!poll_tileset(&mut watcher, |_| false));
+        assert!(!poll_tileset(&mut watcher, |_| false));
+        assert_eq!(poll_event(), None);
+        _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,293 @@
+//! The `Menu` builds window menus in code rather than from a resource script.
+use super::{resource::Resource, winstr::to_pcstr};
+use crate::utils::recent_files::RecentFiles;
+use std::ffi::CString;
+use windows::{
+    core::{Error, Result, PCSTR},
+    Win32::{
+        Foundation::{E_INVALIDARG, HWND},
+        Graphics::Gdi::{DeleteObject, HBITMAP},
+        UI::WindowsAndMessaging::*,
+    },
+};
+
+enum MenuItem {
+    Item {
+        id: u32,
+        text: String,
+        icon: Option<Resource>,
+    },
+    Separator,
+    Submenu {
+        text: String,
+        menu: Menu,
+    },
+}
+
+#[derive(Default)]
+pub(crate) struct Menu {
+    items: Vec<MenuItem>,
+}
+impl Menu {
+    pub(crate) fn new() -> Self {
+        Default::default()
+    }
+    /// Add a command item
+    ///
+    /// The `id` is sent with `WM_COMMAND` when the item is selected
+    ///
+    /// The `icon` is drawn next to the text and must be a bitmap resource,
+    /// `build` fails for icons and cursors
+    pub(crate) fn add_item(&mut self, id: u32, text: &str, icon: Option<Resource>) -> &mut Self {
+        self.items.push(MenuItem::Item {
+            id,
+            text: text.to_string(),
+            icon,
+        });
+        self
+    }
+    /// Add a horizontal dividing line
+    pub(crate) fn add_separator(&mut self) -> &mut Self {
+        self.items.push(MenuItem::Separator);
+        self
+    }
+    /// Add a nested menu opened from an item
+    pub(crate) fn add_submenu(&mut self, text: &str, menu: Menu) -> &mut Self {
+        self.items.push(MenuItem::Submenu {
+            text: text.to_string(),
+            menu,
+        });
+        self
+    }
+    /// Add an item for each recent file, most recent first
+    ///
+    /// Items are numbered from `first_id` in list order
+    pub(crate) fn add_recent_files(&mut self, first_id: u32, recent: &RecentFiles) -> &mut Self {
+        for (offset, file) in recent.list().iter().enumerate() {
+            self.add_item(first_id + offset as u32, &file.display().to_string(), None);
+        }
+        self
+    }
+    /// Create the system menu
+    ///
+    /// The menu is owned by the caller until attached to a window
+    ///
+    /// Item icons are copied into the built menu, free them by
+    /// destroying it with `destroy_menu`
+    ///
+    /// Fails if any item text contains a `\0` or an icon is not a bitmap
+    pub(crate) fn build(&self) -> Result<HMENU> {
+        let menu = unsafe { CreatePopupMenu()? };
+        if let Err(e) = self.append_items(menu) {
+            // Also destroys the submenus already appended
+            destroy_menu(menu);
+            return Err(e);
+        }
+        Ok(menu)
+    }
+    fn append_items(&self, menu: HMENU) -> Result<()> {
+        unsafe {
+            for (position, item) in self.items.iter().enumerate() {
+                match item {
+                    MenuItem::Item { id, text, icon } => {
+                        let (_label, label) = item_text(text)?;
+                        AppendMenuA(menu, MF_STRING, *id as usize, label)?;
+                        if let Some(icon) = icon {
+                            let bitmap = item_bitmap(icon, text)?;
+                            let info = MENUITEMINFOA {
+                                cbSize: std::mem::size_of::<MENUITEMINFOA>() as u32,
+                                fMask: MIIM_BITMAP,
+                                hbmpItem: bitmap,
+                                ..Default::default()
+                            };
+                            if let Err(e) = SetMenuItemInfoA(menu, position as u32, true, &info) {
+                                _ = DeleteObject(bitmap);
+                                return Err(e);
+                            }
+                        }
+                    }
+                    MenuItem::Separator => {
+                        AppendMenuA(menu, MF_SEPARATOR, 0, PCSTR::null())?;
+                    }
+                    MenuItem::Submenu {
+                        text,
+                        menu: submenu,
+                    } => {
+                        let (_text, text) = item_text(text)?;
+                        let submenu = submenu.build()?;
+                        if let Err(e) = AppendMenuA(menu, MF_POPUP, submenu.0 as usize, text) {
+                            destroy_menu(submenu);
+                            return Err(e);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Create the system menu and set it as the menu bar of a window
+    pub(crate) fn attach(&self, window: HWND) -> Result<HMENU> {
+        let menu = self.build()?;
+        if let Err(e) = unsafe { SetMenu(window, menu) } {
+            destroy_menu(menu);
+            return Err(e);
+        }
+        Ok(menu)
+    }
+}
+/// Destroy a menu created by `Menu::build` along with its submenus
+/// and the item bitmaps it owns
+///
+/// A window destroys its menu bar without freeing the bitmaps, remove
+/// the menu with `SetMenu` and destroy it here first
+pub(crate) fn destroy_menu(menu: HMENU) {
+    delete_item_bitmaps(menu);
+    unsafe { _ = DestroyMenu(menu) };
+}
+fn delete_item_bitmaps(menu: HMENU) {
+    unsafe {
+        for position in 0..GetMenuItemCount(menu).max(0) {
+            let mut info = MENUITEMINFOA {
+                cbSize: std::mem::size_of::<MENUITEMINFOA>() as u32,
+                fMask: MIIM_BITMAP | MIIM_SUBMENU,
+                ..Default::default()
+            };
+            if GetMenuItemInfoA(menu, position as u32, true, &mut info).is_err() {
+                continue;
+            }
+            if !info.hSubMenu.is_invalid() {
+                delete_item_bitmaps(info.hSubMenu);
+            }
+            if !info.hbmpItem.is_invalid() {
+                _ = DeleteObject(info.hbmpItem);
+            }
+        }
+    }
+}
+// Copy of an item icon for the built menu to own
+fn item_bitmap(icon: &Resource, text: &str) -> Result<HBITMAP> {
+    if icon.resource_type() != IMAGE_BITMAP {
+        return Err(Error::new(
+            E_INVALIDARG,
+            format!("Menu item icon should be a bitmap: {}", text.escape_debug()),
+        ));
+    }
+    let copy = unsafe { CopyImage(icon.handle(), IMAGE_BITMAP, 0, 0, LR_DEFAULTCOLOR)? };
+    Ok(HBITMAP(copy.0))
+}
+// Item text as a nul terminated string kept alive by the returned buffer
+fn item_text(text: &str) -> Result<(CString, PCSTR)> {
+    to_pcstr(text).ok_or_else(|| {
+        Error::new(
+            E_INVALIDARG,
+            format!(
+                "Menu item text should not contain '\\0': {}",
+                text.escape_debug()
+            ),
+        )
+    })
+}
+
+#[cfg(test)]
+mod menu_tests {
+    use super::*;
+    use crate::window::win::paint::bitmap_info;
+    use windows::Win32::{
+        Foundation::HANDLE,
+        Graphics::Gdi::{CreateCompatibleBitmap, GetDC, ReleaseDC},
+    };
+
+    fn bitmap_icon() -> Resource {
+        let bitmap = unsafe {
+            let screen = GetDC(None);
+            let bitmap = CreateCompatibleBitmap(screen, 16, 16);
+            ReleaseDC(None, screen);
+            bitmap
+        };
+        Resource::new(HANDLE(bitmap.0), IMAGE_BITMAP, true)
+    }
+
+    #[test]
+    fn test_build_menu() {
+        let mut menu = Menu::new();
+        menu.add_item(1, "Erase", None).add_item(2, "Pick", None);
+        let handle = menu.build().unwrap();
+
+        assert_eq!(unsafe { GetMenuItemCount(handle) }, 2);
+        destroy_menu(handle);
+    }
+
+    #[test]
+    fn test_build_menu_with_submenu() {
+        let mut submenu = Menu::new();
+        submenu.add_item(3, "Rotate", None);
+        let mut menu = Menu::new();
+        menu.add_item(1, "Erase", None)
+            .add_separator()
+            .add_submenu("Transform", submenu);
+        let handle = menu.build().unwrap();
+        let submenu = unsafe { GetSubMenu(handle, 2) };
+
+        assert_eq!(unsafe { GetMenuItemCount(handle) }, 3);
+        assert_eq!(unsafe { GetMenuItemCount(submenu) }, 1);
+        destroy_menu(handle);
+    }
+
+    #[test]
+    fn test_build_menu_nul_text() {
+        let mut submenu = Menu::new();
+        submenu.add_item(3, "Rot\0ate", None);
+        let mut menu = Menu::new();
+        menu.add_item(1, "Erase", None)
+            .add_submenu("Transform", submenu);
+
+        assert_eq!(menu.build().unwrap_err().code(), E_INVALIDARG);
+        assert!(Menu::new().add_item(1, "Er\0ase", None).build().is_err());
+    }
+    #[test]
+    fn test_build_menu_with_recent_files() {
+        let mut recent =
+            RecentFiles::load(std::env::temp_dir().join("stellar2d-recent-menu.txt"), 5);
+        recent.push("a.scene");
+        recent.push("b.scene");
+        let mut menu = Menu::new();
+        menu.add_recent_files(100, &recent);
+        let handle = menu.build().unwrap();
+
+        let mut text = [0u8; 16];
+        let len = unsafe { GetMenuStringA(handle, 100, Some(&mut text), MF_BYCOMMAND) };
+        assert_eq!(unsafe { GetMenuItemCount(handle) }, 2);
+        assert_eq!(&text[..len as usize], b"b.scene");
+        destroy_menu(handle);
+    }
+    #[test]
+    fn test_build_menu_icon_copied() {
+        let mut menu = Menu::new();
+        menu.add_item(1, "Erase", Some(bitmap_icon()));
+        let handle = menu.build().unwrap();
+        drop(menu);
+
+        let mut info = MENUITEMINFOA {
+            cbSize: std::mem::size_of::<MENUITEMINFOA>() as u32,
+            fMask: MIIM_BITMAP,
+            ..Default::default()
+        };
+        unsafe { GetMenuItemInfoA(handle, 0, true, &mut info).unwrap() };
+        // The copy outlives the icon and is freed with the menu
+        assert!(bitmap_info(info.hbmpItem).is_some());
+        destroy_menu(handle);
+        assert!(bitmap_info(info.hbmpItem).is_none());
+    }
+    #[test]
+    fn test_build_menu_icon_not_bitmap() {
+        let cursor = unsafe { LoadCursorW(None, IDC_ARROW).unwrap() };
+        let mut menu = Menu::new();
+        menu.add_item(1, "Erase", Some(bitmap_icon())).add_item(
+            2,
+            "Pick",
+            Some(Resource::new(HANDLE(cursor.0), IMAGE_CURSOR, false)),
+        );
+
+        assert_eq!(menu.build().unwrap_err().code(), E_INVALIDARG);
+    }
+}
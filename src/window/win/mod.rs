@@ -1,5 +1,10 @@
+mod clipboard;
+mod error;
+pub mod event;
 mod instance;
+mod menu;
 mod paint;
 mod resource;
 mod window;
 pub mod window_manager;
+mod winstr;
@@ -1,10 +1,356 @@
+use std::{ffi::c_void, fs::File, io, io::Write, mem::size_of};
 use windows::Win32::{
-    Foundation::COLORREF,
-    Graphics::Gdi::{CreateSolidBrush, HBRUSH},
+    Foundation::{COLORREF, RECT},
+    Graphics::Gdi::{
+        CreateCompatibleBitmap, CreateCompatibleDC, CreateHatchBrush, CreatePatternBrush,
+        CreateSolidBrush, DeleteDC, DeleteObject, FillRect, GetDC, GetDIBits, GetObjectA,
+        ReleaseDC, SelectObject, BITMAP, BITMAPFILEHEADER, BITMAPINFO, BITMAPINFOHEADER, BI_RGB,
+        DIB_RGB_COLORS, HATCH_BRUSH_STYLE, HBITMAP, HBRUSH, HDC, HS_BDIAGONAL, HS_CROSS,
+        HS_DIAGCROSS, HS_FDIAGONAL, HS_HORIZONTAL, HS_VERTICAL,
+    },
 };
 
+/// Pack red, green, and blue into the `0x00BBGGRR` layout of a `COLORREF`
+pub(crate) fn rgb(r: u8, g: u8, b: u8) -> COLORREF {
+    COLORREF(((b as u32) << 16) | ((g as u32) << 8) | r as u32)
+}
+
 // Create handle for window paint brush
 fn create_brush(r: u8, g: u8, b: u8) -> HBRUSH {
-    let color = ((b as u32) << 16) | ((g as u32) << 8) | r as u32;
-    unsafe { CreateSolidBrush(COLORREF(color)) }
+    unsafe { CreateSolidBrush(rgb(r, g, b)) }
+}
+
+/// Line pattern of a hatch brush
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HatchStyle {
+    /// `-----`
+    Horizontal,
+    /// `|||||`
+    Vertical,
+    /// `\\\\\`
+    ForwardDiagonal,
+    /// `/////`
+    BackwardDiagonal,
+    /// `+++++`
+    Cross,
+    /// `xxxxx`
+    DiagonalCross,
+}
+impl HatchStyle {
+    fn style(self) -> HATCH_BRUSH_STYLE {
+        match self {
+            HatchStyle::Horizontal => HS_HORIZONTAL,
+            HatchStyle::Vertical => HS_VERTICAL,
+            HatchStyle::ForwardDiagonal => HS_FDIAGONAL,
+            HatchStyle::BackwardDiagonal => HS_BDIAGONAL,
+            HatchStyle::Cross => HS_CROSS,
+            HatchStyle::DiagonalCross => HS_DIAGCROSS,
+        }
+    }
+}
+
+// Create handle for a hatched paint brush with lines of one color
+fn create_hatch_brush(style: HatchStyle, r: u8, g: u8, b: u8) -> HBRUSH {
+    unsafe { CreateHatchBrush(style.style(), rgb(r, g, b)) }
+}
+
+// Create handle for a paint brush repeating a checkerboard of two colors
+fn create_checker_brush(cell: i32, color_a: (u8, u8, u8), color_b: (u8, u8, u8)) -> HBRUSH {
+    let size = cell.max(1) * 2;
+    unsafe {
+        let screen = GetDC(None);
+        let dc = CreateCompatibleDC(screen);
+        let bitmap = CreateCompatibleBitmap(screen, size, size);
+        ReleaseDC(None, screen);
+        let previous = SelectObject(dc, bitmap);
+        let rect = RECT {
+            left: 0,
+            top: 0,
+            right: size,
+            bottom: size,
+        };
+        fill_checkerboard(dc, rect, cell.max(1), color_a, color_b);
+        SelectObject(dc, previous);
+        _ = DeleteDC(dc);
+
+        // The brush keeps its own copy of the bitmap
+        let brush = CreatePatternBrush(bitmap);
+        _ = DeleteObject(bitmap);
+        brush
+    }
+}
+
+/// Fill behind the contents of a window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Background {
+    /// One red, green, and blue color
+    Solid(u8, u8, u8),
+    /// Lines of one red, green, and blue color over the window
+    Hatch(HatchStyle, u8, u8, u8),
+    /// Alternating square cells of two colors
+    Checker {
+        cell: i32,
+        color_a: (u8, u8, u8),
+        color_b: (u8, u8, u8),
+    },
+}
+impl Background {
+    /// Create the brush painting the background
+    ///
+    /// The caller owns the brush until it is given to a window class
+    pub(crate) fn brush(&self) -> HBRUSH {
+        match *self {
+            Background::Solid(r, g, b) => create_brush(r, g, b),
+            Background::Hatch(style, r, g, b) => create_hatch_brush(style, r, g, b),
+            Background::Checker {
+                cell,
+                color_a,
+                color_b,
+            } => create_checker_brush(cell, color_a, color_b),
+        }
+    }
+}
+
+/// Fill a rectangle with alternating square cells of two colors
+///
+/// Cells start at the top left of `rect` and are clipped at its edges
+pub(crate) fn fill_checkerboard(
+    dc: HDC,
+    rect: RECT,
+    cell: i32,
+    color_a: (u8, u8, u8),
+    color_b: (u8, u8, u8),
+) {
+    if cell <= 0 {
+        return;
+    }
+    let brushes = [
+        create_brush(color_a.0, color_a.1, color_a.2),
+        create_brush(color_b.0, color_b.1, color_b.2),
+    ];
+    for (row, top) in (rect.top..rect.bottom).step_by(cell as usize).enumerate() {
+        for (column, left) in (rect.left..rect.right).step_by(cell as usize).enumerate() {
+            let square = RECT {
+                left,
+                top,
+                right: (left + cell).min(rect.right),
+                bottom: (top + cell).min(rect.bottom),
+            };
+            unsafe { FillRect(dc, &square, brushes[(row + column) % 2]) };
+        }
+    }
+    for brush in brushes {
+        unsafe { _ = DeleteObject(brush) };
+    }
+}
+
+// Bytes per pixel row, rows in a bitmap file are padded to a 4 byte boundary
+fn row_stride(width: i32, bit_count: u16) -> usize {
+    (width as usize * bit_count as usize).div_ceil(32) * 4
+}
+
+/// Read the size and format of a bitmap, `None` if the handle is not a valid bitmap
+pub(crate) fn bitmap_info(bitmap: HBITMAP) -> Option<BITMAP> {
+    let mut info = BITMAP::default();
+    let read = unsafe {
+        GetObjectA(
+            bitmap,
+            size_of::<BITMAP>() as i32,
+            Some(&mut info as *mut BITMAP as *mut c_void),
+        )
+    };
+    (read != 0).then_some(info)
+}
+
+/// Write a bitmap to a `.bmp` file as 24bpp bottom-up pixel rows
+pub(crate) fn save_bitmap(bitmap: HBITMAP, path: &str) -> io::Result<()> {
+    let Some(source) = bitmap_info(bitmap) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "save_bitmap() Bitmap handle is not valid",
+        ));
+    };
+
+    let (width, height) = (source.bmWidth, source.bmHeight.abs());
+    let stride = row_stride(width, 24);
+    let mut info = BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+            biSize: size_of::<BITMAPINFOHEADER>() as u32,
+            biWidth: width,
+            // Positive height requests bottom-up rows
+            biHeight: height,
+            biPlanes: 1,
+            biBitCount: 24,
+            biCompression: BI_RGB.0,
+            biSizeImage: (stride * height as usize) as u32,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut pixels = vec![0u8; stride * height as usize];
+    let lines = unsafe {
+        let dc = CreateCompatibleDC(None);
+        let lines = GetDIBits(
+            dc,
+            bitmap,
+            0,
+            height as u32,
+            Some(pixels.as_mut_ptr() as *mut c_void),
+            &mut info,
+            DIB_RGB_COLORS,
+        );
+        _ = DeleteDC(dc);
+        lines
+    };
+    if lines != height {
+        return Err(io::Error::other(
+            "save_bitmap() Failed to read the bitmap pixels",
+        ));
+    }
+
+    let offset = (size_of::<BITMAPFILEHEADER>() + size_of::<BITMAPINFOHEADER>()) as u32;
+    let file_header = BITMAPFILEHEADER {
+        bfType: u16::from_le_bytes(*b"BM"),
+        bfSize: offset + pixels.len() as u32,
+        bfReserved1: 0,
+        bfReserved2: 0,
+        bfOffBits: offset,
+    };
+    let header = info.bmiHeader;
+
+    let mut file = File::create(path)?;
+    // File header
+    file.write_all(&{ file_header.bfType }.to_le_bytes())?;
+    file.write_all(&{ file_header.bfSize }.to_le_bytes())?;
+    file.write_all(&{ file_header.bfReserved1 }.to_le_bytes())?;
+    file.write_all(&{ file_header.bfReserved2 }.to_le_bytes())?;
+    file.write_all(&{ file_header.bfOffBits }.to_le_bytes())?;
+    // Info header
+    file.write_all(&header.biSize.to_le_bytes())?;
+    file.write_all(&header.biWidth.to_le_bytes())?;
+    file.write_all(&header.biHeight.to_le_bytes())?;
+    file.write_all(&header.biPlanes.to_le_bytes())?;
+    file.write_all(&header.biBitCount.to_le_bytes())?;
+    file.write_all(&header.biCompression.to_le_bytes())?;
+    file.write_all(&header.biSizeImage.to_le_bytes())?;
+    file.write_all(&header.biXPelsPerMeter.to_le_bytes())?;
+    file.write_all(&header.biYPelsPerMeter.to_le_bytes())?;
+    file.write_all(&header.biClrUsed.to_le_bytes())?;
+    file.write_all(&header.biClrImportant.to_le_bytes())?;
+    // Pixel rows
+    file.write_all(&pixels)?;
+    file.flush()
+}
+
+#[cfg(test)]
+mod paint_tests {
+    use super::*;
+    use windows::Win32::Graphics::Gdi::GetPixel;
+
+    #[test]
+    fn test_rgb() {
+        assert_eq!(rgb(0x12, 0x34, 0x56), COLORREF(0x0056_3412));
+    }
+    #[test]
+    fn test_fill_checkerboard() {
+        let (white, gray) = ((255, 255, 255), (204, 204, 204));
+        unsafe {
+            let screen = GetDC(None);
+            let dc = CreateCompatibleDC(screen);
+            let bitmap = CreateCompatibleBitmap(screen, 10, 10);
+            ReleaseDC(None, screen);
+            let previous = SelectObject(dc, bitmap);
+
+            let rect = RECT {
+                left: 0,
+                top: 0,
+                right: 10,
+                bottom: 10,
+            };
+            fill_checkerboard(dc, rect, 4, white, gray);
+
+            let white = COLORREF(0xFFFFFF);
+            let gray = COLORREF(0xCCCCCC);
+            assert_eq!(GetPixel(dc, 0, 0), white);
+            assert_eq!(GetPixel(dc, 4, 0), gray);
+            assert_eq!(GetPixel(dc, 0, 4), gray);
+            assert_eq!(GetPixel(dc, 5, 5), white);
+            // Clipped cell in the bottom right corner
+            assert_eq!(GetPixel(dc, 9, 9), white);
+            assert_eq!(GetPixel(dc, 9, 5), gray);
+
+            SelectObject(dc, previous);
+            _ = DeleteObject(bitmap);
+            _ = DeleteDC(dc);
+        }
+    }
+    #[test]
+    fn test_background_brushes() {
+        let backgrounds = [
+            Background::Solid(255, 0, 0),
+            Background::Hatch(HatchStyle::DiagonalCross, 0, 0, 255),
+            Background::Checker {
+                cell: 8,
+                color_a: (255, 255, 255),
+                color_b: (204, 204, 204),
+            },
+        ];
+        for background in backgrounds {
+            let brush = background.brush();
+            assert!(!brush.is_invalid());
+            assert!(unsafe { DeleteObject(brush) }.as_bool());
+        }
+    }
+    #[test]
+    fn test_save_bitmap_round_trip() {
+        use windows::{
+            core::PCSTR,
+            Win32::UI::WindowsAndMessaging::{LoadImageA, IMAGE_BITMAP, LR_LOADFROMFILE},
+        };
+
+        let path =
+            std::env::temp_dir().join(format!("stellar2d-save-bitmap-{}.bmp", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+
+        // Width not divisible by 4 to force row padding
+        let bitmap = unsafe {
+            let screen = GetDC(None);
+            let bitmap = CreateCompatibleBitmap(screen, 3, 2);
+            ReleaseDC(None, screen);
+            bitmap
+        };
+        save_bitmap(bitmap, &path).unwrap();
+        unsafe { _ = DeleteObject(bitmap) };
+
+        let name = format!("{}\0", path);
+        let loaded = unsafe {
+            LoadImageA(
+                None,
+                PCSTR::from_raw(name.as_ptr()),
+                IMAGE_BITMAP,
+                0,
+                0,
+                LR_LOADFROMFILE,
+            )
+        }
+        .unwrap();
+        let loaded = HBITMAP(loaded.0);
+        let info = bitmap_info(loaded).unwrap();
+        unsafe { _ = DeleteObject(loaded) };
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!((info.bmWidth, info.bmHeight), (3, 2));
+    }
+    #[test]
+    fn test_bitmap_info_invalid() {
+        assert!(bitmap_info(HBITMAP(0)).is_none());
+    }
+    #[test]
+    fn test_row_stride_padding() {
+        assert_eq!(row_stride(4, 24), 12);
+        assert_eq!(row_stride(3, 24), 12);
+        assert_eq!(row_stride(1, 24), 4);
+        assert_eq!(row_stride(5, 24), 16);
+        assert_eq!(row_stride(3, 32), 12);
+    }
 }
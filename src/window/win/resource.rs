@@ -1,20 +1,31 @@
-use super::instance::Instance;
+use super::{error::last_error, instance::Instance, paint::bitmap_info, winstr::to_pcstr};
 use crate::utils::logger::Logger;
 use std::{
     borrow::Cow,
+    ffi::{c_void, CString},
+    fmt::{self, Debug},
     fs::metadata,
     io::Write,
+    mem::size_of,
     ops::{BitAnd, BitOr},
     path::Path,
+    rc::Rc,
 };
 use windows::{
     core::{PCSTR, PCWSTR},
     Win32::{
-        Foundation::{HANDLE, HINSTANCE},
+        Foundation::{FALSE, HANDLE, HINSTANCE},
+        Graphics::Gdi::{
+            CreateBitmap, CreateCompatibleBitmap, CreateCompatibleDC, CreateDIBSection, DeleteDC,
+            DeleteObject, GetDC, GetDIBits, ReleaseDC, SelectObject, SetBrushOrgEx,
+            SetStretchBltMode, StretchBlt, BITMAP, BITMAPINFO, BITMAPINFOHEADER, BI_RGB,
+            COLORONCOLOR, DIB_RGB_COLORS, HALFTONE, HBITMAP, SRCCOPY,
+        },
         UI::WindowsAndMessaging::*,
     },
 };
 
+#[derive(Clone, Copy)]
 enum ResourceName<'a> {
     File(&'a str),
     /// Windows OEM Bitmaps
@@ -30,10 +41,37 @@ enum ResourceName<'a> {
     Name(&'a str),
 }
 
+/// Reason a resource failed to load
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResourceError {
+    /// Name is empty, malformed, or unsupported
+    InvalidName,
+    /// `ResourceName::File` has no file extension
+    MissingExtension,
+    /// `ResourceName::File` does not exist
+    FileNotFound,
+    /// The system failed to create a handle
+    LoadFailed,
+    /// `ResourceName` variant can not be used with the load method
+    IncompatibleName,
+}
+
+/// Filtering used when a bitmap is scaled to the set dimensions
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum ScaleMode {
+    /// Repeat pixels to keep hard edges for pixel art
+    #[default]
+    Nearest,
+    /// Average pixels for a smoother result
+    Smooth,
+}
+
 struct ResourceBuilder<'a, T: Write> {
     flags: IMAGE_FLAGS,
     resource_type: GDI_IMAGE_TYPE,
     dimensions: (i32, i32),
+    color_key: Option<(u8, u8, u8)>,
+    scale_mode: ScaleMode,
     name: ResourceName<'a>,
     instance: HINSTANCE,
     logger: Logger<T>,
@@ -46,18 +84,30 @@ impl<'a, T: Write> ResourceBuilder<'a, T> {
             flags: Default::default(),
             resource_type: Default::default(),
             dimensions: Default::default(),
+            color_key: None,
+            scale_mode: Default::default(),
             name: ResourceName::Name(""),
         }
     }
 
     ///  Set the width and height of the icon or image
     ///
-    /// No-op for bitmap
+    /// Bitmaps are scaled with the `ScaleMode`
     fn set_dimensions(&mut self, w: i32, h: i32) -> &mut Self {
         self.dimensions = (w, h);
         self
     }
 
+    /// Set the filtering used to scale a bitmap to its dimensions
+    ///
+    /// Default is `ScaleMode::Nearest`
+    ///
+    /// DIB section and mono bitmaps are scaled by the system instead
+    fn set_scale_mode(&mut self, scale_mode: ScaleMode) -> &mut Self {
+        self.scale_mode = scale_mode;
+        self
+    }
+
     /// Use the system default size for the resource
     fn use_sysdefault(&mut self) -> &mut Self {
         self.flags = self.flags.bitor(LR_DEFAULTSIZE);
@@ -99,6 +149,15 @@ impl<'a, T: Write> ResourceBuilder<'a, T> {
         self
     }
 
+    /// Load bitmap as 32bpp with transparency for every pixel matching
+    /// the red, green, and blue key
+    ///
+    /// Unlike `use_transparent` this works on any color depth
+    fn set_color_key(&mut self, r: u8, g: u8, b: u8) -> &mut Self {
+        self.color_key = Some((r, g, b));
+        self
+    }
+
     /// Set the process to hold the resource
     ///
     /// Default is `this` process
@@ -116,8 +175,8 @@ impl<'a, T: Write> ResourceBuilder<'a, T> {
     /// ## Example
     /// ```
     /// let mut builder = ResourceBuilder::new(Logger::new(&mut buffer, 2));
-    /// let resource1 = builder.set_name(Resource::Name("TestBMP\0")).load()
-    /// let resource2 = builder.set_name(Resource::File("test.bmp\0")).load()
+    /// let resource1 = builder.set_name(Resource::Name("TestBMP")).load()
+    /// let resource2 = builder.set_name(Resource::File("test.bmp")).load()
     ///
     /// assert!(resource1.is_some())
     /// assert!(resource2.is_some())
@@ -127,12 +186,32 @@ impl<'a, T: Write> ResourceBuilder<'a, T> {
         self
     }
 
+    /// Check that a name resolves without loading it
+    ///
+    /// Runs the same validation as a load, so files must exist and names
+    /// and extensions must be supported
+    fn exists(&mut self, name: &ResourceName<'a>) -> bool {
+        // Validation also picks the type and instance for a load
+        let (saved_name, resource_type, instance) = (
+            std::mem::replace(&mut self.name, *name),
+            self.resource_type,
+            self.instance,
+        );
+        let exists = self.name_as_pcstr().is_ok();
+        self.name = saved_name;
+        self.resource_type = resource_type;
+        self.instance = instance;
+        exists
+    }
+
     /// Convert stored `ResourceName` to PCSTR
-    fn name_as_pcstr(&mut self) -> Option<PCSTR> {
+    ///
+    /// Names are copied into the returned buffer which must outlive the pointer
+    fn name_as_pcstr(&mut self) -> Result<(Option<CString>, PCSTR), ResourceError> {
         let name = match self.name {
             ResourceName::File(file) => {
-                if !file.is_empty() {
-                    if file.ends_with('\0') {
+                if !file.trim_end_matches('\0').is_empty() {
+                    if let Some((buffer, pointer)) = to_pcstr(file) {
                         let path = Path::new(file.trim_end_matches('\0'));
                         if let Some(ext) = path.extension() {
                             let ext = ext.to_string_lossy();
@@ -148,19 +227,19 @@ impl<'a, T: Write> ResourceBuilder<'a, T> {
                                     )
                                         .as_str(),
                                     );
-                                    return None;
+                                    return Err(ResourceError::InvalidName);
                                 }
                             }
                         } else {
                             self.logger
                                 .elogln("ResourceBuilder::name_as_pcstr() No file extension");
-                            return None;
+                            return Err(ResourceError::MissingExtension);
                         }
 
                         let path_string = path.to_string_lossy();
                         if !path_string.contains("�") {
                             if metadata(path).is_ok() {
-                                Some(PCSTR(file.as_ptr()))
+                                Ok((Some(buffer), pointer))
                             } else {
                                 self.logger.elogln(
                                     format!(
@@ -169,7 +248,7 @@ impl<'a, T: Write> ResourceBuilder<'a, T> {
                                     )
                                     .as_str(),
                                 );
-                                None
+                                Err(ResourceError::FileNotFound)
                             }
                         } else {
                             self.logger.elogln(
@@ -179,44 +258,42 @@ impl<'a, T: Write> ResourceBuilder<'a, T> {
                                 )
                                 .as_str(),
                             );
-                            None
+                            Err(ResourceError::InvalidName)
                         }
                     } else {
                         self.logger.elogln(
                             format!(
-                                r"ResourceBuilder::name_as_pcstr\(\) Filename needs to end in '\0': {} \n",
-                                file
+                                r"ResourceBuilder::name_as_pcstr() Filename should not contain '\0': {}",
+                                file.escape_debug()
                             )
                             .as_str(),
                         );
-                        None
+                        Err(ResourceError::InvalidName)
                     }
                 } else {
                     self.logger
                         .elogln("ResourceBuilder::name_as_pcstr() Filename can not be empty");
-                    None
+                    Err(ResourceError::InvalidName)
                 }
             }
             ResourceName::WinOIC(id) => {
                 self.resource_type = IMAGE_ICON;
-                self.flags = self.flags.bitor(LR_SHARED);
                 self.instance = Default::default();
 
-                Some(PCSTR(id as *const u8))
+                Ok((None, PCSTR(id as *const u8)))
             }
             ResourceName::WinOCR(id) => {
                 self.resource_type = IMAGE_CURSOR;
-                self.flags = self.flags.bitor(LR_SHARED);
                 self.instance = Default::default();
 
                 let res = match id {
-                    32641u32 => None,
-                    32647u32 => None,
-                    32640u32 => None,
-                    _ => Some(PCSTR(id as *const u8)),
+                    32641u32 => Err(ResourceError::InvalidName),
+                    32647u32 => Err(ResourceError::InvalidName),
+                    32640u32 => Err(ResourceError::InvalidName),
+                    _ => Ok((None, PCSTR(id as *const u8))),
                 };
 
-                if res.is_none() {
+                if res.is_err() {
                     self.logger
                         .elogln("ResourceBuilder::name_as_pcstr() OCR_NO, OCR_SIZE, and OCR_ICOCUR are no-op with ResourceName::WinOCR");
                 }
@@ -225,28 +302,25 @@ impl<'a, T: Write> ResourceBuilder<'a, T> {
             }
             ResourceName::WinOBM(id) => {
                 self.resource_type = IMAGE_BITMAP;
-                self.flags = self.flags.bitor(LR_SHARED);
                 self.instance = Default::default();
 
-                Some(PCSTR(id as *const u8))
+                Ok((None, PCSTR(id as *const u8)))
             }
             ResourceName::WinIDC(id) => {
                 self.resource_type = IMAGE_CURSOR;
-                self.flags = self.flags.bitor(LR_SHARED);
                 self.instance = Default::default();
 
-                Some(PCSTR(id.0 as *const u8))
+                Ok((None, PCSTR(id.0 as *const u8)))
             }
             ResourceName::WinIDI(id) => {
                 self.resource_type = IMAGE_ICON;
-                self.flags = self.flags.bitor(LR_SHARED);
                 self.instance = Default::default();
 
-                Some(PCSTR(id.0 as *const u8))
+                Ok((None, PCSTR(id.0 as *const u8)))
             }
             ResourceName::Name(name) => {
-                if !name.is_empty() {
-                    if name.ends_with('\0') {
+                if !name.trim_end_matches('\0').is_empty() {
+                    if let Some((buffer, pointer)) = to_pcstr(name) {
                         match name.to_uppercase() {
                             n if n.contains("BMP") => self.resource_type = IMAGE_BITMAP,
                             n if n.contains("CUR") => self.resource_type = IMAGE_CURSOR,
@@ -255,34 +329,59 @@ impl<'a, T: Write> ResourceBuilder<'a, T> {
                                 self.logger.elogln(
                                     format!(
                                         "ResourceBuilder::name_as_pcstr() Name is invalid: {}",
-                                        name
+                                        name.trim_end_matches('\0')
                                     )
                                     .as_str(),
                                 );
-                                return None;
+                                return Err(ResourceError::InvalidName);
                             }
                         };
-                        Some(PCSTR(name.as_ptr()))
+                        Ok((Some(buffer), pointer))
                     } else {
                         self.logger.elogln(
                             format!(
-                                r"ResourceBuilder::name_as_pcstr() Name needs to end in '\0': {}",
-                                name
+                                r"ResourceBuilder::name_as_pcstr() Name should not contain '\0': {}",
+                                name.escape_debug()
                             )
                             .as_str(),
                         );
-                        None
+                        Err(ResourceError::InvalidName)
                     }
                 } else {
                     self.logger
                         .elogln("ResourceBuilder::name_as_pcstr() Name can not be empty");
-                    None
+                    Err(ResourceError::InvalidName)
                 }
             }
         };
         name
     }
 
+    // Readable form of the stored `ResourceName` for logs
+    fn name_description(&self) -> String {
+        match self.name {
+            ResourceName::File(file) => format!("File({})", file.trim_end_matches('\0')),
+            ResourceName::Name(name) => format!("Name({})", name.trim_end_matches('\0')),
+            ResourceName::WinOBM(id) => format!("WinOBM({})", id),
+            ResourceName::WinOIC(id) => format!("WinOIC({})", id),
+            ResourceName::WinOCR(id) => format!("WinOCR({})", id),
+            ResourceName::WinIDI(id) => format!("WinIDI({})", id.0 as usize),
+            ResourceName::WinIDC(id) => format!("WinIDC({})", id.0 as usize),
+        }
+    }
+
+    // Flags for loading the stored name
+    //
+    // Files are read from disk and system resources are shared without
+    // changing the flags set on the builder for later loads
+    fn load_flags(&self) -> IMAGE_FLAGS {
+        match self.name {
+            ResourceName::File(_) => self.flags.bitor(LR_LOADFROMFILE),
+            ResourceName::Name(_) => self.flags,
+            _ => self.flags.bitor(LR_SHARED),
+        }
+    }
+
     /// Check if flag is set
     fn is_flag(&self, flag: IMAGE_FLAGS) -> bool {
         self.flags.0.bitand(flag.0) == flag.0
@@ -341,93 +440,500 @@ impl<'a, T: Write> ResourceBuilder<'a, T> {
                 )
             }
         }
+        // Scaling
+        if self.scale_mode != ScaleMode::Nearest
+            && (self.is_flag(LR_CREATEDIBSECTION) || self.is_flag(LR_MONOCHROME))
+        {
+            self.logger.wlogln(
+                "ResourceBuilder::validator() Scale mode is no-op when DIB section or mono is used",
+            )
+        }
+        if self.color_key.is_some() && self.resource_type != IMAGE_BITMAP {
+            self.logger.wlogln(
+                "ResourceBuilder::validator() Color key is no-op when resource type is not 'IMAGE_BITMAP'",
+            )
+        }
     }
 
-    fn load(&mut self) -> Option<Resource> {
-        match self.name {
-            ResourceName::File(_) => {
-                self.flags = self.flags.bitor(LR_LOADFROMFILE);
-            }
-            _ => (),
+    fn try_load(&mut self) -> Result<Resource, ResourceError> {
+        self.logger.logln(
+            format!(
+                "ResourceBuilder::load() Loading resource: {}",
+                self.name_description()
+            )
+            .as_str(),
+        );
+        let (_buffer, name) = self.name_as_pcstr()?;
+        self.validator();
+        let flags = self.load_flags();
+
+        // Bitmaps are loaded at their original size and scaled after, DIB sections
+        // and mono bitmaps are sized by the system to keep their format
+        let scale = self.resource_type == IMAGE_BITMAP
+            && self.dimensions != (0, 0)
+            && !self.is_flag(LR_CREATEDIBSECTION)
+            && !self.is_flag(LR_MONOCHROME);
+        let (width, height) = if scale { (0, 0) } else { self.dimensions };
+        let handle = unsafe {
+            LoadImageA(
+                self.instance,
+                name,
+                self.resource_type,
+                width,
+                height,
+                flags,
+            )
         }
-
-        if let Some(name) = self.name_as_pcstr() {
-            self.validator();
-
-            let handle = unsafe {
-                LoadImageA(
-                    self.instance,
-                    name,
-                    self.resource_type,
-                    self.dimensions.0,
-                    self.dimensions.1,
-                    self.flags,
-                )
+        .ok();
+
+        if let Some(handle) = handle {
+            // Shared resources are owned by the system
+            let mut resource =
+                Resource::new(handle, self.resource_type, flags.bitand(LR_SHARED).0 == 0);
+            if scale {
+                resource = resource
+                    .scaled(self.dimensions, self.scale_mode)
+                    .ok_or_else(|| {
+                        self.logger
+                            .elogln("ResourceBuilder::load() Failed to scale the bitmap");
+                        ResourceError::LoadFailed
+                    })?;
             }
-            .ok();
-
-            if let Some(handle) = handle {
-                Some(Resource::new(handle))
-            } else {
-                self.logger
-                    .elogln("ResourceBuilder::load() Failed to create a handle for the resource");
-                None
+            if let Some(key) = self
+                .color_key
+                .filter(|_| self.resource_type == IMAGE_BITMAP)
+            {
+                resource = resource.color_keyed(key).ok_or_else(|| {
+                    self.logger.elogln(
+                        "ResourceBuilder::load() Failed to apply the color key to the bitmap",
+                    );
+                    ResourceError::LoadFailed
+                })?;
             }
+
+            let (width, height) = resource.dimensions().unwrap_or(self.dimensions);
+            self.logger.logln(
+                format!(
+                    "ResourceBuilder::load() Loaded {} {} with dimensions {}x{} and flags {:#x}",
+                    resource_type_name(self.resource_type),
+                    self.name_description(),
+                    width,
+                    height,
+                    flags.0
+                )
+                .as_str(),
+            );
+            Ok(resource)
         } else {
-            None
+            let (code, message) = last_error();
+            self.logger.elogln(
+                format!(
+                    "ResourceBuilder::load() Failed to create a handle for the resource: {} (code {})",
+                    message, code
+                )
+                .as_str(),
+            );
+            Err(ResourceError::LoadFailed)
         }
     }
 
-    fn load_icon(&mut self) -> Option<HICON> {
+    fn load(&mut self) -> Option<Resource> {
+        self.try_load().ok()
+    }
+
+    fn try_load_icon(&mut self) -> Result<HICON, ResourceError> {
         match self.name {
             ResourceName::WinIDI(_) | ResourceName::WinOIC(_) => {
-                let name = self.name_as_pcstr().unwrap_or(PCSTR::null());
+                self.logger.logln(
+                    format!(
+                        "ResourceBuilder::load_icon() Loading icon: {}",
+                        self.name_description()
+                    )
+                    .as_str(),
+                );
+                let (_buffer, name) = self.name_as_pcstr()?;
                 if let Some(handle) = unsafe { LoadIconA(self.instance, name) }.ok() {
-                    Some(handle)
+                    self.logger.logln(
+                        format!(
+                            "ResourceBuilder::load_icon() Loaded icon: {}",
+                            self.name_description()
+                        )
+                        .as_str(),
+                    );
+                    Ok(handle)
                 } else {
+                    let (code, message) = last_error();
                     self.logger.elogln(
-                        "ResourceBuilder::load_icon() Failed to create a handle for the icon",
+                        format!(
+                            "ResourceBuilder::load_icon() Failed to create a handle for the icon: {} (code {})",
+                            message, code
+                        )
+                        .as_str(),
                     );
-                    None
+                    Err(ResourceError::LoadFailed)
                 }
             }
             _ => {
                 self.logger.elogln(
                     "ResourceBuilder::load_icon() 'ResourceName::WinIDI' or 'ResourceName::WinOIC' should be used",
                 );
-                None
+                Err(ResourceError::IncompatibleName)
             }
         }
     }
 
-    fn load_cursor(&mut self) -> Option<HCURSOR> {
+    fn load_icon(&mut self) -> Option<HICON> {
+        self.try_load_icon().ok()
+    }
+
+    fn try_load_cursor(&mut self) -> Result<HCURSOR, ResourceError> {
         match self.name {
             ResourceName::WinIDC(_) | ResourceName::WinOCR(_) => {
-                let name = self.name_as_pcstr().unwrap_or(PCSTR::null());
+                self.logger.logln(
+                    format!(
+                        "ResourceBuilder::load_cursor() Loading cursor: {}",
+                        self.name_description()
+                    )
+                    .as_str(),
+                );
+                let (_buffer, name) = self.name_as_pcstr()?;
                 if let Some(handle) = unsafe { LoadCursorA(self.instance, name) }.ok() {
-                    Some(handle)
+                    self.logger.logln(
+                        format!(
+                            "ResourceBuilder::load_cursor() Loaded cursor: {}",
+                            self.name_description()
+                        )
+                        .as_str(),
+                    );
+                    Ok(handle)
                 } else {
+                    let (code, message) = last_error();
                     self.logger.elogln(
-                        "ResourceBuilder::load_cursor() Failed to create a handle for the cursor",
+                        format!(
+                            "ResourceBuilder::load_cursor() Failed to create a handle for the cursor: {} (code {})",
+                            message, code
+                        )
+                        .as_str(),
                     );
-                    None
+                    Err(ResourceError::LoadFailed)
                 }
             }
             _ => {
                 self.logger.elogln(
                     "ResourceBuilder::load_cursor() 'ResourceName::WinIDC' or 'ResourceName::WinOCR' should be used",
                 );
+                Err(ResourceError::IncompatibleName)
+            }
+        }
+    }
+
+    fn load_cursor(&mut self) -> Option<HCURSOR> {
+        self.try_load_cursor().ok()
+    }
+
+    /// Create a cursor showing a bitmap resource with the hotspot at
+    /// `hotspot_x` and `hotspot_y` pixels from its top left
+    fn create_cursor_from_bitmap(
+        &mut self,
+        resource: &Resource,
+        hotspot_x: u32,
+        hotspot_y: u32,
+    ) -> Option<Resource> {
+        let Some((width, height)) = resource.dimensions() else {
+            self.logger.elogln(
+                "ResourceBuilder::create_cursor_from_bitmap() Resource type should be 'IMAGE_BITMAP'",
+            );
+            return None;
+        };
+        if hotspot_x >= width as u32 || hotspot_y >= height as u32 {
+            self.logger.wlogln(
+                format!(
+                    "ResourceBuilder::create_cursor_from_bitmap() Hotspot {},{} is outside the {}x{} bitmap",
+                    hotspot_x, hotspot_y, width, height
+                )
+                .as_str(),
+            );
+        }
+
+        // An empty mask keeps every pixel of the color bitmap, rows are word aligned
+        let mask_bits = vec![0u8; (width as usize).div_ceil(16) * 2 * height as usize];
+        let cursor = unsafe {
+            let mask = CreateBitmap(
+                width,
+                height,
+                1,
+                1,
+                Some(mask_bits.as_ptr() as *const c_void),
+            );
+            let info = ICONINFO {
+                fIcon: FALSE,
+                xHotspot: hotspot_x,
+                yHotspot: hotspot_y,
+                hbmMask: mask,
+                hbmColor: HBITMAP(resource.id.0),
+            };
+            // The cursor keeps its own copy of the bitmaps, read the
+            // error before the cleanup can overwrite it
+            let cursor = CreateIconIndirect(&info).map_err(|_| last_error());
+            _ = DeleteObject(mask);
+            cursor
+        };
+
+        match cursor {
+            Ok(cursor) => Some(Resource::new(HANDLE(cursor.0), IMAGE_CURSOR, true)),
+            Err((code, message)) => {
+                self.logger.elogln(
+                    format!(
+                        "ResourceBuilder::create_cursor_from_bitmap() Failed to create a handle for the cursor: {} (code {})",
+                        message, code
+                    )
+                    .as_str(),
+                );
                 None
             }
         }
     }
 }
-struct Resource {
+/// A loaded resource that destroys its handle when dropped
+///
+/// Shared system resources are left to the system
+pub(crate) struct Resource {
     id: HANDLE,
+    resource_type: GDI_IMAGE_TYPE,
+    owned: bool,
+}
+/// A `Resource` used in several places, destroyed when the last clone drops
+pub(crate) type SharedResource = Rc<Resource>;
+impl Drop for Resource {
+    fn drop(&mut self) {
+        if !self.owned {
+            return;
+        }
+        unsafe {
+            match self.resource_type {
+                IMAGE_ICON => _ = DestroyIcon(HICON(self.id.0)),
+                IMAGE_CURSOR => _ = DestroyCursor(HCURSOR(self.id.0)),
+                _ => _ = DeleteObject(HBITMAP(self.id.0)),
+            }
+        }
+    }
 }
+impl Debug for Resource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Resource");
+        // Handle values are only useful while debugging
+        if cfg!(debug_assertions) {
+            debug.field("handle", &format_args!("{:#x}", self.id.0));
+        } else {
+            debug.field("handle", &format_args!("<masked>"));
+        }
+        debug
+            .field(
+                "resource_type",
+                &format_args!("{}", resource_type_name(self.resource_type)),
+            )
+            .finish()
+    }
+}
+/// Resources are the same when they share a handle
+impl PartialEq for Resource {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+impl Eq for Resource {}
 impl Resource {
-    fn new(id: HANDLE) -> Self {
-        Self { id }
+    /// Wrap a loaded handle, destroyed on drop if `owned`
+    pub(crate) fn new(id: HANDLE, resource_type: GDI_IMAGE_TYPE, owned: bool) -> Self {
+        Self {
+            id,
+            resource_type,
+            owned,
+        }
+    }
+    /// Share the resource without copying the handle
+    pub(crate) fn into_shared(self) -> SharedResource {
+        Rc::new(self)
+    }
+    /// The system handle of the resource
+    pub(crate) fn handle(&self) -> HANDLE {
+        self.id
+    }
+    pub(crate) fn resource_type(&self) -> GDI_IMAGE_TYPE {
+        self.resource_type
+    }
+    fn bitmap_info(&self) -> Option<BITMAP> {
+        if self.resource_type != IMAGE_BITMAP {
+            return None;
+        }
+        bitmap_info(HBITMAP(self.id.0))
+    }
+    /// The width and height of a bitmap resource
+    ///
+    /// `None` for icons and cursors
+    pub(crate) fn dimensions(&self) -> Option<(i32, i32)> {
+        self.bitmap_info()
+            .map(|info| (info.bmWidth, info.bmHeight.abs()))
+    }
+    /// Read the pixels of a bitmap resource as RGBA rows from top to bottom
+    ///
+    /// Bitmaps without an alpha channel are read as fully opaque
+    ///
+    /// `None` for icons and cursors
+    pub(crate) fn pixels(&self) -> Option<Vec<[u8; 4]>> {
+        let bitmap = self.bitmap_info()?;
+        let (width, height) = (bitmap.bmWidth, bitmap.bmHeight.abs());
+        let mut info = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: width,
+                // Negative height requests top-down rows
+                biHeight: -height,
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut pixels = vec![[0u8; 4]; (width * height) as usize];
+        let lines = unsafe {
+            let dc = CreateCompatibleDC(None);
+            let lines = GetDIBits(
+                dc,
+                HBITMAP(self.id.0),
+                0,
+                height as u32,
+                Some(pixels.as_mut_ptr() as *mut c_void),
+                &mut info,
+                DIB_RGB_COLORS,
+            );
+            _ = DeleteDC(dc);
+            lines
+        };
+        if lines != height {
+            return None;
+        }
+
+        let opaque = bitmap.bmBitsPixel < 32;
+        for pixel in pixels.iter_mut() {
+            // Stored as BGRA
+            pixel.swap(0, 2);
+            if opaque {
+                pixel[3] = 255;
+            }
+        }
+        Some(pixels)
+    }
+    /// Copy the bitmap stretched to the dimensions
+    ///
+    /// A zero width or height keeps the original
+    fn scaled(&self, dimensions: (i32, i32), scale_mode: ScaleMode) -> Option<Self> {
+        let (source_width, source_height) = self.dimensions()?;
+        let width = if dimensions.0 == 0 {
+            source_width
+        } else {
+            dimensions.0
+        };
+        let height = if dimensions.1 == 0 {
+            source_height
+        } else {
+            dimensions.1
+        };
+        unsafe {
+            let screen = GetDC(None);
+            let source = CreateCompatibleDC(screen);
+            let target = CreateCompatibleDC(screen);
+            let bitmap = CreateCompatibleBitmap(screen, width, height);
+            ReleaseDC(None, screen);
+            let previous_source = SelectObject(source, HBITMAP(self.id.0));
+            let previous_target = SelectObject(target, bitmap);
+
+            match scale_mode {
+                ScaleMode::Nearest => {
+                    SetStretchBltMode(target, COLORONCOLOR);
+                }
+                ScaleMode::Smooth => {
+                    SetStretchBltMode(target, HALFTONE);
+                    // Brush origin must be reset after switching to halftone
+                    _ = SetBrushOrgEx(target, 0, 0, None);
+                }
+            }
+            let stretched = StretchBlt(
+                target,
+                0,
+                0,
+                width,
+                height,
+                source,
+                0,
+                0,
+                source_width,
+                source_height,
+                SRCCOPY,
+            )
+            .as_bool();
+
+            SelectObject(source, previous_source);
+            SelectObject(target, previous_target);
+            _ = DeleteDC(source);
+            _ = DeleteDC(target);
+            if !stretched {
+                _ = DeleteObject(bitmap);
+                return None;
+            }
+            Some(Self::new(HANDLE(bitmap.0), IMAGE_BITMAP, true))
+        }
+    }
+    /// Copy the bitmap to 32bpp where pixels matching the key are fully transparent
+    ///
+    /// Colors are premultiplied by alpha as `AlphaBlend` expects, so keyed pixels are all zero
+    fn color_keyed(&self, key: (u8, u8, u8)) -> Option<Self> {
+        let (width, height) = self.dimensions()?;
+        let mut pixels = self.pixels()?;
+        for pixel in pixels.iter_mut() {
+            if (pixel[0], pixel[1], pixel[2]) == key {
+                *pixel = [0, 0, 0, 0];
+            } else {
+                pixel[3] = 255;
+                // Stored as BGRA
+                pixel.swap(0, 2);
+            }
+        }
+
+        let info = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: width,
+                // Negative height requests top-down rows
+                biHeight: -height,
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut bits: *mut c_void = std::ptr::null_mut();
+        let bitmap =
+            unsafe { CreateDIBSection(None, &info, DIB_RGB_COLORS, &mut bits, None, 0).ok()? };
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                pixels.as_ptr() as *const u8,
+                bits as *mut u8,
+                pixels.len() * 4,
+            );
+        }
+        Some(Self::new(HANDLE(bitmap.0), IMAGE_BITMAP, true))
+    }
+}
+
+fn resource_type_name(resource_type: GDI_IMAGE_TYPE) -> &'static str {
+    match resource_type {
+        IMAGE_BITMAP => "IMAGE_BITMAP",
+        IMAGE_CURSOR => "IMAGE_CURSOR",
+        IMAGE_ICON => "IMAGE_ICON",
+        _ => "UNKNOWN",
     }
 }
 
@@ -480,7 +986,7 @@ mod resource_builder_tests {
             let cursor2 = builder.set_name(ResourceName::WinOCR(7821)).load_cursor();
 
             assert_log_cnt(
-                r"\[ERROR\] \d{4}-\d{1,2}-\d{1,2} \d{1,2}:\d{1,2}:\d{1,2}.\d{1,3}: ResourceBuilder::load_cursor\(\) Failed to create a handle for the cursor\n",
+                r"\[ERROR\] \d{4}-\d{1,2}-\d{1,2} \d{1,2}:\d{1,2}:\d{1,2}.\d{1,3}: ResourceBuilder::load_cursor\(\) Failed to create a handle for the cursor: .+ \(code \d+\)\n",
                 &buffer,
                 2,
             );
@@ -547,7 +1053,7 @@ mod resource_builder_tests {
             let icon2 = builder.set_name(ResourceName::WinOIC(7821)).load_icon();
 
             assert_log_cnt(
-                r"\[ERROR\] \d{4}-\d{1,2}-\d{1,2} \d{1,2}:\d{1,2}:\d{1,2}.\d{1,3}: ResourceBuilder::load_icon\(\) Failed to create a handle for the icon\n",
+                r"\[ERROR\] \d{4}-\d{1,2}-\d{1,2} \d{1,2}:\d{1,2}:\d{1,2}.\d{1,3}: ResourceBuilder::load_icon\(\) Failed to create a handle for the icon: .+ \(code \d+\)\n",
                 &buffer,
                 2,
             );
@@ -588,6 +1094,20 @@ mod resource_builder_tests {
             assert!(icon5.is_none());
         }
 
+        #[test]
+        fn test_load_failed_system_error() {
+            let mut buffer = Vec::new();
+
+            let mut builder = ResourceBuilder::new(Logger::new(&mut buffer, 1));
+            let resource = builder.set_name(ResourceName::Name("TestTestBMP")).load();
+
+            assert_log(
+                r"ResourceBuilder::load\(\) Failed to create a handle for the resource: \S.* \(code [1-9]\d*\)\n",
+                &buffer,
+            );
+            assert!(resource.is_none())
+        }
+
         #[test]
         fn test_load_failed() {
             let mut buffer = Vec::new();
@@ -603,6 +1123,78 @@ mod resource_builder_tests {
         }
     }
 
+    mod try_load_tests {
+        use super::*;
+
+        #[test]
+        fn test_try_load_invalid_name() {
+            let mut buffer = Vec::new();
+
+            let mut builder = ResourceBuilder::new(Logger::new(&mut buffer, 1));
+            let resource1 = builder.set_name(ResourceName::Name("")).try_load();
+            let resource2 = builder.set_name(ResourceName::Name("Test\0")).try_load();
+            let resource3 = builder.set_name(ResourceName::File("foo.txt\0")).try_load();
+
+            assert_eq!(resource1.err(), Some(ResourceError::InvalidName));
+            assert_eq!(resource2.err(), Some(ResourceError::InvalidName));
+            assert_eq!(resource3.err(), Some(ResourceError::InvalidName));
+        }
+
+        #[test]
+        fn test_try_load_missing_extension() {
+            let mut buffer = Vec::new();
+
+            let mut builder = ResourceBuilder::new(Logger::new(&mut buffer, 1));
+            let resource = builder.set_name(ResourceName::File("foo\0")).try_load();
+
+            assert_eq!(resource.err(), Some(ResourceError::MissingExtension));
+        }
+
+        #[test]
+        fn test_try_load_file_not_found() {
+            let mut buffer = Vec::new();
+
+            let mut builder = ResourceBuilder::new(Logger::new(&mut buffer, 1));
+            let resource = builder.set_name(ResourceName::File("foo.bmp\0")).try_load();
+
+            assert_eq!(resource.err(), Some(ResourceError::FileNotFound));
+        }
+
+        #[test]
+        fn test_try_load_failed() {
+            let mut buffer = Vec::new();
+
+            let mut builder = ResourceBuilder::new(Logger::new(&mut buffer, 1));
+            let resource = builder
+                .set_name(ResourceName::Name("TestTestBMP\0"))
+                .try_load();
+            let icon = builder.set_name(ResourceName::WinOIC(7821)).try_load_icon();
+            let cursor = builder
+                .set_name(ResourceName::WinOCR(7821))
+                .try_load_cursor();
+
+            assert_eq!(resource.err(), Some(ResourceError::LoadFailed));
+            assert_eq!(icon.err(), Some(ResourceError::LoadFailed));
+            assert_eq!(cursor.err(), Some(ResourceError::LoadFailed));
+        }
+
+        #[test]
+        fn test_try_load_incompatible_name() {
+            let mut buffer = Vec::new();
+
+            let mut builder = ResourceBuilder::new(Logger::new(&mut buffer, 1));
+            let icon = builder
+                .set_name(ResourceName::WinIDC(IDC_ARROW))
+                .try_load_icon();
+            let cursor = builder
+                .set_name(ResourceName::WinIDI(IDI_APPLICATION))
+                .try_load_cursor();
+
+            assert_eq!(icon.err(), Some(ResourceError::IncompatibleName));
+            assert_eq!(cursor.err(), Some(ResourceError::IncompatibleName));
+        }
+    }
+
     mod flags_tests {
         use super::*;
 
@@ -680,6 +1272,58 @@ mod resource_builder_tests {
     mod name_as_pcstr_test {
         use super::*;
 
+        #[test]
+        fn test_exists_present_file() {
+            let mut buffer = Vec::new();
+
+            let mut builder = ResourceBuilder::new(Logger::new(&mut buffer, 1));
+            let exists = builder.exists(&ResourceName::File("tests\\resources\\sample.bmp"));
+
+            assert!(exists);
+            assert!(&buffer.is_empty());
+        }
+
+        #[test]
+        fn test_exists_missing_file() {
+            let mut buffer = Vec::new();
+
+            let mut builder = ResourceBuilder::new(Logger::new(&mut buffer, 1));
+            let exists = builder.exists(&ResourceName::File("missing.bmp"));
+
+            assert!(!exists);
+            assert_log(
+                r"ResourceBuilder::name_as_pcstr\(\) File does not exist: missing.bmp\n",
+                &buffer,
+            );
+        }
+
+        #[test]
+        fn test_exists_bad_extension() {
+            let mut buffer = Vec::new();
+
+            let mut builder = ResourceBuilder::new(Logger::new(&mut buffer, 1));
+            let exists = builder.exists(&ResourceName::File("foo.txt"));
+
+            assert!(!exists);
+            assert_log(
+                r"ResourceBuilder::name_as_pcstr\(\) File extension is not valid: .txt\n",
+                &buffer,
+            );
+        }
+
+        #[test]
+        fn test_exists_keeps_builder_state() {
+            let mut buffer = Vec::new();
+
+            let mut builder = ResourceBuilder::new(Logger::new(&mut buffer, 1));
+            builder.set_name(ResourceName::File("tests\\resources\\sample.ico"));
+            assert!(builder.exists(&ResourceName::WinOBM(OBM_CLOSE)));
+
+            assert!(!builder.is_flag(LR_SHARED));
+            assert_eq!(builder.resource_type, GDI_IMAGE_TYPE::default());
+            assert!(builder.load().is_some());
+        }
+
         #[test]
         fn test_load() {
             let mut buffer = Vec::new();
@@ -730,7 +1374,22 @@ mod resource_builder_tests {
             let resource: Option<Resource> = builder.set_name(ResourceName::Name("test")).load();
 
             assert_log(
-                r"\[ERROR\] \d{4}-\d{1,2}-\d{1,2} \d{1,2}:\d{1,2}:\d{1,2}.\d{1,3}: ResourceBuilder::name_as_pcstr\(\) Name needs to end in '\0': foot.txt\n",
+                r"\[ERROR\] \d{4}-\d{1,2}-\d{1,2} \d{1,2}:\d{1,2}:\d{1,2}.\d{1,3}: ResourceBuilder::name_as_pcstr\(\) Name is invalid: test\n",
+                &buffer,
+            );
+            assert!(resource.is_none())
+        }
+
+        #[test]
+        fn test_name_as_pcstr_name_interior_null() {
+            let mut buffer = Vec::new();
+
+            let mut builder = ResourceBuilder::new(Logger::new(&mut buffer, 1));
+            let resource: Option<Resource> =
+                builder.set_name(ResourceName::Name("Test\0BMP")).load();
+
+            assert_log(
+                r"\[ERROR\] \d{4}-\d{1,2}-\d{1,2} \d{1,2}:\d{1,2}:\d{1,2}.\d{1,3}: ResourceBuilder::name_as_pcstr\(\) Name should not contain '\\0': Test\\0BMP\n",
                 &buffer,
             );
             assert!(resource.is_none())
@@ -790,10 +1449,24 @@ mod resource_builder_tests {
             let mut buffer = Vec::new();
 
             let mut builder = ResourceBuilder::new(Logger::new(&mut buffer, 1));
-            let resource: Option<Resource> = builder.set_name(ResourceName::File("foo.txt")).load();
+            let resource: Option<Resource> = builder
+                .set_name(ResourceName::File("tests\\resources\\sample.bmp"))
+                .load();
+
+            assert!(&buffer.is_empty());
+            assert!(resource.is_some())
+        }
+
+        #[test]
+        fn test_name_as_pcstr_file_interior_null() {
+            let mut buffer = Vec::new();
+
+            let mut builder = ResourceBuilder::new(Logger::new(&mut buffer, 1));
+            let resource: Option<Resource> =
+                builder.set_name(ResourceName::File("foo\0.bmp")).load();
 
             assert_log(
-                r"\[ERROR\] \d{4}-\d{1,2}-\d{1,2} \d{1,2}:\d{1,2}:\d{1,2}.\d{1,3}: ResourceBuilder::name_as_pcstr\(\) Filename needs to end in '\0': foot.txt\n",
+                r"\[ERROR\] \d{4}-\d{1,2}-\d{1,2} \d{1,2}:\d{1,2}:\d{1,2}.\d{1,3}: ResourceBuilder::name_as_pcstr\(\) Filename should not contain '\\0': foo\\0.bmp\n",
                 &buffer,
             );
             assert!(resource.is_none())
@@ -978,6 +1651,43 @@ mod resource_builder_tests {
             assert!(&buffer.is_empty());
         }
 
+        #[test]
+        fn test_use_dib_scaled_keeps_dib() {
+            let mut buffer = Vec::new();
+
+            let mut builder = ResourceBuilder::new(Logger::new(&mut buffer, 2));
+            let resource = builder
+                .set_name(ResourceName::File("tests\\resources\\sample.bmp"))
+                .use_dib()
+                .set_dimensions(10, 12)
+                .load()
+                .unwrap();
+
+            let info = bitmap_info(HBITMAP(resource.handle().0)).unwrap();
+            // Only DIB sections expose their pixel bits
+            assert!(!info.bmBits.is_null());
+            assert_eq!((info.bmWidth, info.bmHeight), (10, 12));
+        }
+
+        #[test]
+        fn test_validator_scale_mode_no_op_with_dib() {
+            let mut buffer = Vec::new();
+
+            let mut builder = ResourceBuilder::new(Logger::new(&mut buffer, 2));
+            let resource = builder
+                .set_name(ResourceName::File("tests\\resources\\sample.bmp"))
+                .use_dib()
+                .set_dimensions(10, 12)
+                .set_scale_mode(ScaleMode::Smooth)
+                .load();
+
+            assert!(resource.is_some());
+            assert_log(
+                r"\[WARNING\] \d{4}-\d{1,2}-\d{1,2} \d{1,2}:\d{1,2}:\d{1,2}.\d{1,3}: ResourceBuilder::validator\(\) Scale mode is no-op when DIB section or mono is used\n",
+                &buffer,
+            );
+        }
+
         #[test]
         fn test_validator_no_op_3d_or_vga() {
             let mut buffer = Vec::new();
@@ -1017,4 +1727,319 @@ mod resource_builder_tests {
             assert!(resource2.is_some());
         }
     }
+
+    mod lifecycle_tests {
+        use super::*;
+
+        #[test]
+        fn test_load_info_logs() {
+            let mut buffer = Vec::new();
+            let resource = ResourceBuilder::new(Logger::new(&mut buffer, 3))
+                .set_name(ResourceName::File("tests\\resources\\sample.bmp"))
+                .load();
+
+            assert!(resource.is_some());
+            assert_log(
+                r"\[INFO\] \d{4}-\d{1,2}-\d{1,2} \d{1,2}:\d{1,2}:\d{1,2}.\d{1,3}: ResourceBuilder::load\(\) Loading resource: File\(tests\\resources\\sample.bmp\)\n",
+                &buffer,
+            );
+            assert_log(
+                r"\[INFO\] \d{4}-\d{1,2}-\d{1,2} \d{1,2}:\d{1,2}:\d{1,2}.\d{1,3}: ResourceBuilder::load\(\) Loaded IMAGE_BITMAP File\(tests\\resources\\sample.bmp\) with dimensions \d+x\d+ and flags 0x10\n",
+                &buffer,
+            );
+        }
+        #[test]
+        fn test_load_icon_info_logs() {
+            let mut buffer = Vec::new();
+            let icon = ResourceBuilder::new(Logger::new(&mut buffer, 3))
+                .set_name(ResourceName::WinOIC(OIC_HAND))
+                .load_icon();
+
+            assert!(icon.is_some());
+            assert_log(
+                r"ResourceBuilder::load_icon\(\) Loaded icon: WinOIC\(32513\)\n",
+                &buffer,
+            );
+        }
+        #[test]
+        fn test_load_silent_below_info() {
+            let mut buffer = Vec::new();
+            let resource = ResourceBuilder::new(Logger::new(&mut buffer, 2))
+                .set_name(ResourceName::File("tests\\resources\\sample.bmp"))
+                .load();
+
+            assert!(resource.is_some());
+            assert!(&buffer.is_empty());
+        }
+    }
+
+    mod shared_tests {
+        use super::*;
+
+        #[test]
+        fn test_shared_drops_with_last_clone() {
+            let mut buffer = Vec::new();
+            let shared = ResourceBuilder::new(Logger::new(&mut buffer, 1))
+                .set_name(ResourceName::File("tests\\resources\\sample.bmp"))
+                .load()
+                .unwrap()
+                .into_shared();
+            let handle = shared.handle();
+            let clone1 = Rc::clone(&shared);
+            let clone2 = Rc::clone(&shared);
+            drop(shared);
+            drop(clone1);
+
+            assert_eq!(Rc::strong_count(&clone2), 1);
+            assert!(clone2.dimensions().is_some());
+
+            drop(clone2);
+            assert!(bitmap_info(HBITMAP(handle.0)).is_none());
+        }
+        #[test]
+        fn test_shared_system_resource_not_destroyed() {
+            let mut buffer = Vec::new();
+            let mut builder = ResourceBuilder::new(Logger::new(&mut buffer, 1));
+            let resource = builder.set_name(ResourceName::WinOBM(OBM_CLOSE)).load();
+            drop(resource);
+
+            let resource = builder.set_name(ResourceName::WinOBM(OBM_CLOSE)).load();
+            assert!(resource.unwrap().dimensions().is_some());
+        }
+        #[test]
+        fn test_file_owned_after_system_resource() {
+            let mut buffer = Vec::new();
+            let mut builder = ResourceBuilder::new(Logger::new(&mut buffer, 1));
+            let system = builder.set_name(ResourceName::WinOBM(OBM_CLOSE)).load();
+            let file = builder
+                .set_name(ResourceName::File("tests\\resources\\sample.bmp"))
+                .load()
+                .unwrap();
+            let handle = file.handle();
+            drop(file);
+
+            assert!(system.is_some());
+            assert!(bitmap_info(HBITMAP(handle.0)).is_none());
+        }
+    }
+
+    mod cursor_tests {
+        use super::*;
+
+        #[test]
+        fn test_create_cursor_from_bitmap_hotspot() {
+            let mut buffer = Vec::new();
+            let mut builder = ResourceBuilder::new(Logger::new(&mut buffer, 2));
+            let bitmap = builder
+                .set_name(ResourceName::File("tests\\resources\\sample.bmp"))
+                .load()
+                .unwrap();
+            let cursor = builder.create_cursor_from_bitmap(&bitmap, 2, 3).unwrap();
+
+            let mut info = ICONINFO::default();
+            unsafe {
+                GetIconInfo(HICON(cursor.handle().0), &mut info).unwrap();
+                _ = DeleteObject(info.hbmMask);
+                _ = DeleteObject(info.hbmColor);
+            }
+            assert!(&buffer.is_empty());
+            assert!(!info.fIcon.as_bool());
+            assert_eq!((info.xHotspot, info.yHotspot), (2, 3));
+            assert!(format!("{:?}", cursor).contains("IMAGE_CURSOR"));
+        }
+        #[test]
+        fn test_create_cursor_from_bitmap_not_bitmap() {
+            let mut buffer = Vec::new();
+            let mut builder = ResourceBuilder::new(Logger::new(&mut buffer, 1));
+            let icon = builder
+                .set_name(ResourceName::WinOIC(OIC_HAND))
+                .load()
+                .unwrap();
+            let cursor = builder.create_cursor_from_bitmap(&icon, 0, 0);
+
+            assert!(cursor.is_none());
+            assert_log(
+                r"ResourceBuilder::create_cursor_from_bitmap\(\) Resource type should be 'IMAGE_BITMAP'\n",
+                &buffer,
+            );
+        }
+    }
+
+    mod debug_tests {
+        use super::*;
+
+        #[test]
+        fn test_debug_format() {
+            let mut buffer = Vec::new();
+            let resource = ResourceBuilder::new(Logger::new(&mut buffer, 1))
+                .set_name(ResourceName::WinOBM(OBM_CLOSE))
+                .load()
+                .unwrap();
+
+            let expected =
+                Regex::new(r"^Resource \{ handle: 0x[0-9a-f]+, resource_type: IMAGE_BITMAP \}$")
+                    .unwrap();
+            assert!(expected.is_match(&format!("{:?}", resource)));
+        }
+        #[test]
+        fn test_eq_by_handle() {
+            let mut buffer = Vec::new();
+            let mut builder = ResourceBuilder::new(Logger::new(&mut buffer, 1));
+            // Shared system bitmaps hand back the same handle
+            let resource1 = builder.set_name(ResourceName::WinOBM(OBM_CLOSE)).load();
+            let resource2 = builder.set_name(ResourceName::WinOBM(OBM_CLOSE)).load();
+            let resource3 = builder.set_name(ResourceName::WinOBM(OBM_BTSIZE)).load();
+
+            assert!(resource1.is_some());
+            assert_eq!(resource1, resource2);
+            assert_ne!(resource1, resource3);
+        }
+    }
+
+    mod pixels_tests {
+        use super::*;
+        use crate::window::win::paint::save_bitmap;
+        use windows::Win32::{
+            Foundation::COLORREF,
+            Graphics::Gdi::{
+                CreateCompatibleBitmap, DeleteObject, GetDC, ReleaseDC, SelectObject, SetPixel,
+            },
+        };
+
+        #[test]
+        fn test_pixels_bitmap() {
+            let mut buffer = Vec::new();
+            let path = std::env::temp_dir().join("stellar2d-pixels.bmp");
+            let path = path.to_string_lossy().to_string();
+
+            unsafe {
+                let screen = GetDC(None);
+                let dc = CreateCompatibleDC(screen);
+                let bitmap = CreateCompatibleBitmap(screen, 2, 2);
+                ReleaseDC(None, screen);
+                let previous = SelectObject(dc, bitmap);
+                // COLORREF is stored as 0x00BBGGRR
+                SetPixel(dc, 0, 0, COLORREF(0x000000FF));
+                SetPixel(dc, 1, 0, COLORREF(0x0000FF00));
+                SetPixel(dc, 0, 1, COLORREF(0x00FF0000));
+                SetPixel(dc, 1, 1, COLORREF(0x00FFFFFF));
+                SelectObject(dc, previous);
+                _ = DeleteDC(dc);
+
+                save_bitmap(bitmap, &path).unwrap();
+                _ = DeleteObject(bitmap);
+            }
+
+            let name = format!("{}\0", path);
+            let resource = ResourceBuilder::new(Logger::new(&mut buffer, 1))
+                .set_name(ResourceName::File(&name))
+                .load()
+                .unwrap();
+
+            assert_eq!(resource.dimensions(), Some((2, 2)));
+            assert_eq!(
+                resource.pixels().unwrap(),
+                vec![
+                    [255, 0, 0, 255],
+                    [0, 255, 0, 255],
+                    [0, 0, 255, 255],
+                    [255, 255, 255, 255]
+                ]
+            );
+        }
+        #[test]
+        fn test_color_key() {
+            let mut buffer = Vec::new();
+            let path = std::env::temp_dir().join("stellar2d-color-key.bmp");
+            let path = path.to_string_lossy().to_string();
+
+            unsafe {
+                let screen = GetDC(None);
+                let dc = CreateCompatibleDC(screen);
+                let bitmap = CreateCompatibleBitmap(screen, 2, 1);
+                ReleaseDC(None, screen);
+                let previous = SelectObject(dc, bitmap);
+                SetPixel(dc, 0, 0, COLORREF(0x00FF00FF));
+                SetPixel(dc, 1, 0, COLORREF(0x0000FF00));
+                SelectObject(dc, previous);
+                _ = DeleteDC(dc);
+
+                save_bitmap(bitmap, &path).unwrap();
+                _ = DeleteObject(bitmap);
+            }
+
+            let name = format!("{}\0", path);
+            let resource = ResourceBuilder::new(Logger::new(&mut buffer, 1))
+                .set_name(ResourceName::File(&name))
+                .set_color_key(255, 0, 255)
+                .load()
+                .unwrap();
+
+            assert!(&buffer.is_empty());
+            assert_eq!(
+                resource.pixels().unwrap(),
+                vec![[0, 0, 0, 0], [0, 255, 0, 255]]
+            );
+        }
+        #[test]
+        fn test_color_key_not_bitmap_warning() {
+            let mut buffer = Vec::new();
+            let resource = ResourceBuilder::new(Logger::new(&mut buffer, 2))
+                .set_name(ResourceName::WinOIC(OIC_HAND))
+                .set_color_key(255, 0, 255)
+                .load();
+
+            assert!(resource.is_some());
+            assert_log(
+                r"ResourceBuilder::validator\(\) Color key is no-op when resource type is not 'IMAGE_BITMAP'",
+                &buffer,
+            );
+        }
+        #[test]
+        fn test_scale_nearest() {
+            let mut buffer = Vec::new();
+            let path = std::env::temp_dir().join("stellar2d-scale-nearest.bmp");
+            let path = path.to_string_lossy().to_string();
+
+            unsafe {
+                let screen = GetDC(None);
+                let dc = CreateCompatibleDC(screen);
+                let bitmap = CreateCompatibleBitmap(screen, 2, 1);
+                ReleaseDC(None, screen);
+                let previous = SelectObject(dc, bitmap);
+                SetPixel(dc, 0, 0, COLORREF(0x00000000));
+                SetPixel(dc, 1, 0, COLORREF(0x00FFFFFF));
+                SelectObject(dc, previous);
+                _ = DeleteDC(dc);
+
+                save_bitmap(bitmap, &path).unwrap();
+                _ = DeleteObject(bitmap);
+            }
+
+            let resource = ResourceBuilder::new(Logger::new(&mut buffer, 1))
+                .set_name(ResourceName::File(&path))
+                .set_dimensions(4, 2)
+                .set_scale_mode(ScaleMode::Nearest)
+                .load()
+                .unwrap();
+
+            let (black, white) = ([0, 0, 0, 255], [255, 255, 255, 255]);
+            assert_eq!(resource.dimensions(), Some((4, 2)));
+            assert_eq!(
+                resource.pixels().unwrap(),
+                vec![black, black, white, white, black, black, white, white]
+            );
+        }
+        #[test]
+        fn test_pixels_not_bitmap() {
+            let mut buffer = Vec::new();
+            let resource = ResourceBuilder::new(Logger::new(&mut buffer, 1))
+                .set_name(ResourceName::WinOIC(OIC_HAND))
+                .load()
+                .unwrap();
+
+            assert!(resource.dimensions().is_none());
+            assert!(resource.pixels().is_none());
+        }
+    }
 }
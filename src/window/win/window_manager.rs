@@ -1,22 +1,38 @@
 //! The `WindowManager` is responsible for creating, managing, and destroying windows.
 //! The `WindowManager` abstracts away the registering of a window class
 //! Compatible with `Windows` only; all other platforms will be no-op.
-use super::{instance::Instance, window::Window};
+use super::{
+    error::last_error,
+    event::{push_event, Event},
+    instance::Instance,
+    paint::{rgb, Background},
+    window::Window,
+    winstr::to_pcstr,
+};
+use crate::utils::logger::Logger;
 use std::{
     ffi::CString,
-    ops::{BitAnd, BitOr},
+    io::Write,
+    ops::{BitAnd, BitOr, Not},
     sync::Arc,
 };
 use windows::{
     core::*,
     Win32::{
         Foundation::*,
-        Graphics::Gdi::{ValidateRect, HBRUSH},
-        UI::WindowsAndMessaging::*,
+        Graphics::Gdi::{
+            ClientToScreen, DeleteObject, GetMonitorInfoA, MonitorFromPoint, MonitorFromWindow,
+            ValidateRect, HBRUSH, MONITORINFO, MONITOR_DEFAULTTONEAREST,
+        },
+        UI::{
+            HiDpi::{SetProcessDpiAwarenessContext, DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2},
+            Shell::{DragAcceptFiles, DragFinish, DragQueryFileA, HDROP},
+            WindowsAndMessaging::*,
+        },
     },
 };
-#[derive(Debug, Default)]
-pub struct WindowManagerBuilder<'a> {
+#[derive(Debug)]
+pub struct WindowManagerBuilder<'a, T: Write> {
     style: WNDCLASS_STYLES,
     lpfnWndProc: WNDPROC,
     metadata: i32,
@@ -25,16 +41,35 @@ pub struct WindowManagerBuilder<'a> {
     hIcon: HICON,
     hCursor: HCURSOR,
     hbrBackground: HBRUSH,
+    background: Option<Background>,
     menuname: Option<&'a str>,
     classname: &'a str,
+    logger: Logger<T>,
 }
-impl<'a> WindowManagerBuilder<'a> {
-    pub fn new() -> Self {
+impl<'a, T: Write> WindowManagerBuilder<'a, T> {
+    pub fn new(logger: Logger<T>) -> Self {
         Self {
+            logger,
             instance: Instance::this(),
-            ..Default::default()
+            style: Default::default(),
+            lpfnWndProc: Default::default(),
+            metadata: Default::default(),
+            window_metadata: Default::default(),
+            hIcon: Default::default(),
+            hCursor: Default::default(),
+            hbrBackground: Default::default(),
+            background: None,
+            menuname: Default::default(),
+            classname: Default::default(),
         }
     }
+    /// Set the fill painted behind the contents of each window
+    ///
+    /// Defaults to no background, leaving erasing to the window
+    pub fn set_background(&mut self, background: Background) -> &mut Self {
+        self.background = Some(background);
+        self
+    }
     /// Set the process to control the manager
     ///
     /// Defaults to `this` process if setting is ignored
@@ -61,16 +96,31 @@ impl<'a> WindowManagerBuilder<'a> {
         self.style = self.style.bitor(CS_BYTEALIGNCLIENT);
         self
     }
+    /// Stop aligning the managers children window's client area by byte boundary
+    pub fn disable_align_byte_client(&mut self) -> &mut Self {
+        self.style = self.style.bitand(CS_BYTEALIGNCLIENT.not());
+        self
+    }
     /// Aligns the managers children window's by byte boundary on x-axis
     pub fn align_byte_window(&mut self) -> &mut Self {
         self.style = self.style.bitor(CS_BYTEALIGNWINDOW);
         self
     }
+    /// Stop aligning the managers children window's by byte boundary
+    pub fn disable_align_byte_window(&mut self) -> &mut Self {
+        self.style = self.style.bitand(CS_BYTEALIGNWINDOW.not());
+        self
+    }
     /// Listen for double clicks within any windows in manager
     pub fn listen_to_dbclick(&mut self) -> &mut Self {
         self.style = self.style.bitor(CS_DBLCLKS);
         self
     }
+    /// Stop listening for double clicks within any windows in manager
+    pub fn disable_dbclick(&mut self) -> &mut Self {
+        self.style = self.style.bitand(CS_DBLCLKS.not());
+        self
+    }
     /// Enable drop shadow effect
     ///
     /// Windows created from the manager must be top-level windows (parent or root); they may not be child windows.
@@ -78,6 +128,11 @@ impl<'a> WindowManagerBuilder<'a> {
         self.style = self.style.bitor(CS_DROPSHADOW);
         self
     }
+    /// Disable drop shadow effect
+    pub fn disable_drop_shadow(&mut self) -> &mut Self {
+        self.style = self.style.bitand(CS_DROPSHADOW.not());
+        self
+    }
     /// Make manager global
     ///
     /// see more - Application Global Class
@@ -85,6 +140,11 @@ impl<'a> WindowManagerBuilder<'a> {
         self.style = self.style.bitor(CS_GLOBALCLASS);
         self
     }
+    /// Make manager local to the process
+    pub fn disable_global(&mut self) -> &mut Self {
+        self.style = self.style.bitand(CS_GLOBALCLASS.not());
+        self
+    }
     /// Listen for a movement or size adjustment change in the height of the client area
     ///
     /// The entire window will be redrawn
@@ -92,6 +152,11 @@ impl<'a> WindowManagerBuilder<'a> {
         self.style = self.style.bitor(CS_VREDRAW);
         self
     }
+    /// Stop redrawing the entire window on height changes of the client area
+    pub fn disable_vert(&mut self) -> &mut Self {
+        self.style = self.style.bitand(CS_VREDRAW.not());
+        self
+    }
     /// Listen for a movement or size adjustment change in the width of the client area.
     ///
     /// The entire window will be redrawn
@@ -99,24 +164,38 @@ impl<'a> WindowManagerBuilder<'a> {
         self.style = self.style.bitor(CS_HREDRAW);
         self
     }
+    /// Stop redrawing the entire window on width changes of the client area
+    pub fn disable_hori(&mut self) -> &mut Self {
+        self.style = self.style.bitand(CS_HREDRAW.not());
+        self
+    }
     /// Disable close on a window menu
     pub fn disable_close(&mut self) -> &mut Self {
         self.style = self.style.bitor(CS_NOCLOSE);
         self
     }
+    /// Enable close on a window menu
+    pub fn enable_close(&mut self) -> &mut Self {
+        self.style = self.style.bitand(CS_NOCLOSE.not());
+        self
+    }
+    /// Reset every style set on the manager
+    pub fn clear_style(&mut self) -> &mut Self {
+        self.style = Default::default();
+        self
+    }
     // Check if a single device context has already been set
-    fn is_dc_set(&self, class1: WNDCLASS_STYLES, class2: WNDCLASS_STYLES) -> bool {
+    fn is_dc_set(&mut self, class1: WNDCLASS_STYLES, class2: WNDCLASS_STYLES) -> bool {
         let class1_dc = self.style.bitand(class1) != WNDCLASS_STYLES(0);
         let class2_dc = self.style.bitand(class2) != WNDCLASS_STYLES(0);
         if class1_dc || class2_dc {
-            let class = if class1_dc {
-                stringify!(class1_dc)
-            } else {
-                stringify!(class2_dc)
-            };
-            format!(
-                "[WARNING] The device context has already been set to '{}'",
-                class
+            let class = if class1_dc { class1 } else { class2 };
+            self.logger.wlogln(
+                format!(
+                    "WindowManagerBuilder::is_dc_set() The device context has already been set to '{}'",
+                    dc_style_name(class)
+                )
+                .as_str(),
             );
             return true;
         }
@@ -154,6 +233,11 @@ impl<'a> WindowManagerBuilder<'a> {
         self.style = self.style.bitor(CS_SAVEBITS);
         self
     }
+    /// Stop redrawing obscured screen images using cache bitmaps
+    pub fn disable_save_bitmap(&mut self) -> &mut Self {
+        self.style = self.style.bitand(CS_SAVEBITS.not());
+        self
+    }
     /// Allocate bytes of memory to store metadata per window
     pub fn allocate_window_metadata(&mut self, bytes: i32) -> &mut Self {
         self.window_metadata = bytes;
@@ -166,35 +250,96 @@ impl<'a> WindowManagerBuilder<'a> {
         self.metadata = bytes;
         self
     }
-    pub fn build(&self) -> WindowManager {
-        assert!(
-            !self.classname.is_empty(),
-            "[Error] Window Manager name can not be empty"
-        );
+    /// Register the manager without panicking on invalid settings
+    pub fn try_build(&self) -> std::result::Result<WindowManager<'_>, BuildError> {
+        if self.classname.is_empty() {
+            return Err(BuildError::EmptyName);
+        }
+        // The buffers must outlive the registration
+        let (_classname, classname) = to_pcstr(self.classname).ok_or(BuildError::InvalidName)?;
+        let menuname = match self.menuname {
+            Some("") => return Err(BuildError::EmptyMenuName),
+            Some(menuname) => Some(to_pcstr(menuname).ok_or(BuildError::InvalidMenuName)?),
+            None => None,
+        };
         let mut class = WNDCLASSA::default();
-        class.lpszClassName = PCSTR::from_raw(self.classname.as_ptr());
-        if let Some(menuname) = self.menuname {
-            assert!(
-                !menuname.is_empty(),
-                "[Error] Window Manager Menu name can not be empty"
-            );
-            class.lpszMenuName = PCSTR::from_raw(self.menuname.unwrap().as_ptr());
+        class.lpszClassName = classname;
+        if let Some((_, menuname)) = &menuname {
+            class.lpszMenuName = *menuname;
         }
         class.hInstance = self.instance;
         class.style = self.style;
         class.cbClsExtra = self.metadata;
         class.cbWndExtra = self.window_metadata;
-        // class.hbrBackground =
+        // The class owns the brush once registered
+        class.hbrBackground = self
+            .background
+            .map(|background| background.brush())
+            .unwrap_or(self.hbrBackground);
         // class.hCursor =
         // class.hIcon =
         // class.lpfnWndProc =
         let atom = unsafe { RegisterClassA(&class) };
-        assert!(
-            atom != 0,
-            "[Error] Window Manager '{}' already exists",
-            self.classname
-        );
-        WindowManager::new(&self.classname)
+        if atom == 0 {
+            let (code, _) = last_error();
+            if self.background.is_some() {
+                unsafe { _ = DeleteObject(class.hbrBackground) };
+            }
+            if code == ERROR_CLASS_ALREADY_EXISTS.0 {
+                return Err(BuildError::ClassAlreadyExists);
+            }
+            return Err(BuildError::RegisterFailed(code));
+        }
+        Ok(WindowManager::new(&self.classname))
+    }
+    pub fn build(&self) -> WindowManager<'_> {
+        match self.try_build() {
+            Ok(manager) => manager,
+            Err(BuildError::EmptyName) => panic!("[Error] Window Manager name can not be empty"),
+            Err(BuildError::EmptyMenuName) => {
+                panic!("[Error] Window Manager Menu name can not be empty")
+            }
+            Err(BuildError::InvalidName) => {
+                panic!("[Error] Window Manager name should not contain '\\0'")
+            }
+            Err(BuildError::InvalidMenuName) => {
+                panic!("[Error] Window Manager Menu name should not contain '\\0'")
+            }
+            Err(BuildError::ClassAlreadyExists) => {
+                panic!("[Error] Window Manager '{}' already exists", self.classname)
+            }
+            Err(BuildError::RegisterFailed(code)) => panic!(
+                "[Error] Window Manager '{}' failed to register: {} (code {})",
+                self.classname,
+                HRESULT::from_win32(code).message().trim_end(),
+                code
+            ),
+        }
+    }
+}
+/// Reason a `WindowManager` failed to build
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildError {
+    /// Manager name is empty
+    EmptyName,
+    /// Menu name was set but is empty
+    EmptyMenuName,
+    /// Manager name contains an interior `\0`
+    InvalidName,
+    /// Menu name contains an interior `\0`
+    InvalidMenuName,
+    /// A manager with the same name is already registered
+    ClassAlreadyExists,
+    /// The system refused to register the manager with the error code
+    RegisterFailed(u32),
+}
+// Name of a device context class style
+fn dc_style_name(style: WNDCLASS_STYLES) -> &'static str {
+    match style {
+        CS_OWNDC => "CS_OWNDC",
+        CS_CLASSDC => "CS_CLASSDC",
+        CS_PARENTDC => "CS_PARENTDC",
+        _ => "UNKNOWN",
     }
 }
 #[derive(Debug, Default)]
@@ -210,6 +355,329 @@ impl<'a> WindowManager<'a> {
         }
     }
 }
+// Window property marking a window as double buffered
+const DOUBLE_BUFFERED: PCSTR = s!("Stellar2D.DoubleBuffered");
+/// Suppress background erasing for a double buffered window
+///
+/// The back buffer paints the whole client area so erasing it first only causes flicker
+pub fn set_double_buffered(window: HWND, enabled: bool) {
+    unsafe {
+        if enabled {
+            _ = SetPropA(window, DOUBLE_BUFFERED, HANDLE(1));
+        } else {
+            _ = RemovePropA(window, DOUBLE_BUFFERED);
+        }
+    }
+}
+/// Check if a window is double buffered
+pub fn is_double_buffered(window: HWND) -> bool {
+    unsafe { GetPropA(window, DOUBLE_BUFFERED).0 != 0 }
+}
+// Window property holding the last dpi reported to a window
+const DPI: PCSTR = s!("Stellar2D.Dpi");
+/// Dots per inch of a monitor at 100% scale
+pub const DEFAULT_DPI: u32 = 96;
+/// Let windows rescale themselves for the dpi of each monitor instead of
+/// the system stretching them
+///
+/// Call once at startup before any window is created
+pub fn enable_dpi_awareness() -> bool {
+    unsafe { SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2).is_ok() }
+}
+/// The dpi last reported to a window, `DEFAULT_DPI` until it changes
+pub fn window_dpi(window: HWND) -> u32 {
+    match unsafe { GetPropA(window, DPI) }.0 {
+        0 => DEFAULT_DPI,
+        dpi => dpi as u32,
+    }
+}
+// Window property holding the style and placement saved before going fullscreen
+const FULLSCREEN: PCSTR = s!("Stellar2D.Fullscreen");
+// Style and placement to restore when leaving fullscreen
+struct SavedWindow {
+    style: isize,
+    placement: WINDOWPLACEMENT,
+}
+/// Switch a window to borderless fullscreen on its monitor or back
+///
+/// The style and placement are saved on enable and restored on disable
+pub fn set_fullscreen(window: HWND, enabled: bool) {
+    if enabled == is_fullscreen(window) {
+        return;
+    }
+    unsafe {
+        if enabled {
+            let mut saved = SavedWindow {
+                style: GetWindowLongPtrA(window, GWL_STYLE),
+                placement: WINDOWPLACEMENT {
+                    length: std::mem::size_of::<WINDOWPLACEMENT>() as u32,
+                    ..Default::default()
+                },
+            };
+            let mut monitor = MONITORINFO {
+                cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+                ..Default::default()
+            };
+            if GetWindowPlacement(window, &mut saved.placement).is_err()
+                || !GetMonitorInfoA(
+                    MonitorFromWindow(window, MONITOR_DEFAULTTONEAREST),
+                    &mut monitor,
+                )
+                .as_bool()
+            {
+                return;
+            }
+            let style = WINDOW_STYLE(saved.style as u32);
+            // The window owns the saved state until it leaves fullscreen or is destroyed
+            _ = SetPropA(
+                window,
+                FULLSCREEN,
+                HANDLE(Box::into_raw(Box::new(saved)) as isize),
+            );
+            SetWindowLongPtrA(
+                window,
+                GWL_STYLE,
+                style.bitand(WS_OVERLAPPEDWINDOW.not()).bitor(WS_POPUP).0 as isize,
+            );
+            let bounds = monitor.rcMonitor;
+            _ = SetWindowPos(
+                window,
+                HWND_TOP,
+                bounds.left,
+                bounds.top,
+                bounds.right - bounds.left,
+                bounds.bottom - bounds.top,
+                SWP_FRAMECHANGED | SWP_NOOWNERZORDER,
+            );
+        } else if let Some(saved) = take_saved_window(window) {
+            SetWindowLongPtrA(window, GWL_STYLE, saved.style);
+            _ = SetWindowPlacement(window, &saved.placement);
+            _ = SetWindowPos(
+                window,
+                None,
+                0,
+                0,
+                0,
+                0,
+                SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_NOOWNERZORDER | SWP_FRAMECHANGED,
+            );
+        }
+    }
+}
+/// Check if a window is in borderless fullscreen
+pub fn is_fullscreen(window: HWND) -> bool {
+    unsafe { GetPropA(window, FULLSCREEN).0 != 0 }
+}
+// Remove and free the state saved by `set_fullscreen`
+fn take_saved_window(window: HWND) -> Option<Box<SavedWindow>> {
+    match unsafe { RemovePropA(window, FULLSCREEN) } {
+        Ok(saved) if saved.0 != 0 => Some(unsafe { Box::from_raw(saved.0 as *mut SavedWindow) }),
+        _ => None,
+    }
+}
+// Remove the window properties set by the helpers in this module
+//
+// Properties have to be removed before the window is destroyed
+fn remove_window_props(window: HWND) {
+    unsafe {
+        _ = RemovePropA(window, DOUBLE_BUFFERED);
+        _ = RemovePropA(window, DPI);
+        _ = RemovePropA(window, CURSOR);
+    }
+    // Free the saved state of a window destroyed while fullscreen
+    take_saved_window(window);
+}
+// Current layered key color, opacity, and flags of a window
+fn layered_attributes(window: HWND) -> (COLORREF, u8, LAYERED_WINDOW_ATTRIBUTES_FLAGS) {
+    let (mut key, mut alpha, mut flags) = (COLORREF(0), 255, LAYERED_WINDOW_ATTRIBUTES_FLAGS(0));
+    unsafe {
+        if GetLayeredWindowAttributes(window, Some(&mut key), Some(&mut alpha), Some(&mut flags))
+            .is_err()
+        {
+            // Not layered yet
+            return (COLORREF(0), 255, LAYERED_WINDOW_ATTRIBUTES_FLAGS(0));
+        }
+    }
+    (key, alpha, flags)
+}
+// Layer the window while any transparency is in use, otherwise restore the style
+fn set_layered(window: HWND, key: COLORREF, alpha: u8, flags: LAYERED_WINDOW_ATTRIBUTES_FLAGS) {
+    unsafe {
+        let style = WINDOW_EX_STYLE(GetWindowLongPtrA(window, GWL_EXSTYLE) as u32);
+        if flags.0 == 0 {
+            SetWindowLongPtrA(
+                window,
+                GWL_EXSTYLE,
+                style.bitand(WS_EX_LAYERED.not()).0 as isize,
+            );
+        } else {
+            SetWindowLongPtrA(window, GWL_EXSTYLE, style.bitor(WS_EX_LAYERED).0 as isize);
+            _ = SetLayeredWindowAttributes(window, key, alpha, flags);
+        }
+    }
+}
+/// Set the opacity of the whole window from 0 (invisible) to 255 (opaque)
+///
+/// An opaque window without a transparent color is no longer layered
+pub fn set_opacity(window: HWND, alpha: u8) {
+    let (key, _, flags) = layered_attributes(window);
+    let flags = if alpha == 255 {
+        flags.bitand(LWA_ALPHA.not())
+    } else {
+        flags.bitor(LWA_ALPHA)
+    };
+    set_layered(window, key, alpha, flags);
+}
+/// Make every pixel of the red, green, and blue color fully transparent
+pub fn set_color_key(window: HWND, r: u8, g: u8, b: u8) {
+    let (_, alpha, flags) = layered_attributes(window);
+    set_layered(window, rgb(r, g, b), alpha, flags.bitor(LWA_COLORKEY));
+}
+/// Stop treating the transparent color as transparent
+pub fn clear_color_key(window: HWND) {
+    let (key, alpha, flags) = layered_attributes(window);
+    set_layered(window, key, alpha, flags.bitand(LWA_COLORKEY.not()));
+}
+// Window position that centers the client area in the work area
+//
+// `window` and `client` are in screen coordinates so the frame around
+// the client area is kept out of the centering
+fn centered_position(work_area: &RECT, window: &RECT, client: &RECT) -> (i32, i32) {
+    let (client_width, client_height) = (client.right - client.left, client.bottom - client.top);
+    let (frame_left, frame_top) = (client.left - window.left, client.top - window.top);
+    (
+        work_area.left + (work_area.right - work_area.left - client_width) / 2 - frame_left,
+        work_area.top + (work_area.bottom - work_area.top - client_height) / 2 - frame_top,
+    )
+}
+/// Center the client area of a window on the monitor under the cursor
+///
+/// The taskbar and docked bars are left out of the monitor area
+pub fn center_on_active_monitor(window: HWND) {
+    unsafe {
+        let mut cursor = POINT::default();
+        if GetCursorPos(&mut cursor).is_err() {
+            return;
+        }
+        let mut monitor = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        if !GetMonitorInfoA(
+            MonitorFromPoint(cursor, MONITOR_DEFAULTTONEAREST),
+            &mut monitor,
+        )
+        .as_bool()
+        {
+            return;
+        }
+        let (mut rect, mut client) = (RECT::default(), RECT::default());
+        if GetWindowRect(window, &mut rect).is_err() || GetClientRect(window, &mut client).is_err()
+        {
+            return;
+        }
+        // Client rect is relative to its own top left corner
+        let mut origin = POINT::default();
+        _ = ClientToScreen(window, &mut origin);
+        let client = RECT {
+            left: origin.x,
+            top: origin.y,
+            right: origin.x + client.right,
+            bottom: origin.y + client.bottom,
+        };
+        let (x, y) = centered_position(&monitor.rcWork, &rect, &client);
+        _ = SetWindowPos(
+            window,
+            None,
+            x,
+            y,
+            0,
+            0,
+            SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE,
+        );
+    }
+}
+// Window property holding the cursor shown over the client area
+const CURSOR: PCSTR = s!("Stellar2D.Cursor");
+/// Show a cursor over the client area of a window in place of the class cursor
+///
+/// Call again to switch cursors, e.g. with one from `ResourceBuilder::load_cursor`,
+/// a null cursor goes back to the class cursor
+pub fn set_cursor(window: HWND, cursor: HCURSOR) {
+    unsafe {
+        if cursor.is_invalid() {
+            _ = RemovePropA(window, CURSOR);
+        } else {
+            _ = SetPropA(window, CURSOR, HANDLE(cursor.0));
+        }
+    }
+}
+/// The cursor set for a window, `None` while it uses the class cursor
+pub fn window_cursor(window: HWND) -> Option<HCURSOR> {
+    match unsafe { GetPropA(window, CURSOR) }.0 {
+        0 => None,
+        cursor => Some(HCURSOR(cursor)),
+    }
+}
+/// Let files dragged from the shell be dropped on a window
+///
+/// `wndproc` reads the dropped paths from `WM_DROPFILES`
+pub fn accept_files(window: HWND, accept: bool) {
+    unsafe { DragAcceptFiles(window, BOOL::from(accept)) };
+}
+// Paths of the files in a shell drop, in the order they were dropped
+fn dropped_files(drop: HDROP) -> Vec<String> {
+    unsafe {
+        // An index of 0xFFFFFFFF asks for the number of files
+        (0..DragQueryFileA(drop, u32::MAX, None))
+            .map(|index| {
+                // The length leaves out the nul terminator
+                let mut path = vec![0; DragQueryFileA(drop, index, None) as usize + 1];
+                let len = DragQueryFileA(drop, index, Some(&mut path)) as usize;
+                path.truncate(len);
+                String::from_utf8_lossy(&path).into_owned()
+            })
+            .collect()
+    }
+}
+// Check a pointer sized value at `offset` fits in the extra bytes the class
+// allocates, `allocated` picks the per window or the per class bytes
+fn metadata_fits(window: HWND, allocated: GET_CLASS_LONG_INDEX, offset: i32) -> bool {
+    let size = unsafe { GetClassLongPtrA(window, allocated) };
+    offset >= 0 && offset as usize + std::mem::size_of::<isize>() <= size
+}
+/// Store a pointer sized value in the bytes reserved for each window by
+/// `WindowManagerBuilder::allocate_window_metadata`
+///
+/// `offset` is in bytes, nothing is stored if the value does not fit
+pub fn set_window_metadata(window: HWND, offset: i32, value: isize) -> bool {
+    if !metadata_fits(window, GCL_CBWNDEXTRA, offset) {
+        return false;
+    }
+    unsafe { SetWindowLongPtrA(window, WINDOW_LONG_PTR_INDEX(offset), value) };
+    true
+}
+/// Read a value stored by `set_window_metadata`
+pub fn window_metadata(window: HWND, offset: i32) -> Option<isize> {
+    metadata_fits(window, GCL_CBWNDEXTRA, offset)
+        .then(|| unsafe { GetWindowLongPtrA(window, WINDOW_LONG_PTR_INDEX(offset)) })
+}
+/// Store a pointer sized value in the bytes reserved for the class of a window by
+/// `WindowManagerBuilder::allocate_metadata`
+///
+/// The value is shared by every window of the class
+pub fn set_class_metadata(window: HWND, offset: i32, value: isize) -> bool {
+    if !metadata_fits(window, GCL_CBCLSEXTRA, offset) {
+        return false;
+    }
+    unsafe { SetClassLongPtrA(window, GET_CLASS_LONG_INDEX(offset), value) };
+    true
+}
+/// Read a value stored by `set_class_metadata`
+pub fn class_metadata(window: HWND, offset: i32) -> Option<isize> {
+    metadata_fits(window, GCL_CBCLSEXTRA, offset)
+        .then(|| unsafe { GetClassLongPtrA(window, GET_CLASS_LONG_INDEX(offset)) } as isize)
+}
 pub extern "system" fn wndproc(
     window: HWND,
     message: u32,
@@ -221,13 +689,62 @@ pub extern "system" fn wndproc(
             WM_PAINT => {
                 println!("WM_PAINT");
                 _ = ValidateRect(window, None);
+                push_event(Event::Paint);
+                LRESULT(0)
+            }
+            WM_ERASEBKGND if is_double_buffered(window) => LRESULT(1),
+            WM_SIZE => {
+                push_event(Event::Resize {
+                    width: (lparam.0 & 0xFFFF) as u32,
+                    height: ((lparam.0 >> 16) & 0xFFFF) as u32,
+                });
+                LRESULT(0)
+            }
+            WM_KEYDOWN => {
+                push_event(Event::key_down(wparam.0, lparam.0));
+                LRESULT(0)
+            }
+            WM_DPICHANGED => {
+                let dpi = (wparam.0 & 0xFFFF) as u32;
+                _ = SetPropA(window, DPI, HANDLE(dpi as isize));
+                // Suggested size and position for the new dpi
+                let rect = &*(lparam.0 as *const RECT);
+                _ = SetWindowPos(
+                    window,
+                    None,
+                    rect.left,
+                    rect.top,
+                    rect.right - rect.left,
+                    rect.bottom - rect.top,
+                    SWP_NOZORDER | SWP_NOACTIVATE,
+                );
+                push_event(Event::DpiChanged { dpi });
                 LRESULT(0)
             }
             WM_DESTROY => {
                 println!("WM_DESTROY");
+                push_event(Event::Close);
                 PostQuitMessage(0);
                 LRESULT(0)
             }
+            WM_NCDESTROY => {
+                remove_window_props(window);
+                DefWindowProcA(window, message, wparam, lparam)
+            }
+            // The frame keeps its sizing cursors
+            WM_SETCURSOR if (lparam.0 & 0xFFFF) as u32 == HTCLIENT => match window_cursor(window) {
+                Some(cursor) => {
+                    SetCursor(cursor);
+                    LRESULT(1)
+                }
+                None => DefWindowProcA(window, message, wparam, lparam),
+            },
+            WM_DROPFILES => {
+                let drop = HDROP(wparam.0 as isize);
+                push_event(Event::FilesDropped(dropped_files(drop)));
+                DragFinish(drop);
+                LRESULT(0)
+            }
             _ => DefWindowProcA(window, message, wparam, lparam),
         }
     }
@@ -235,31 +752,37 @@ pub extern "system" fn wndproc(
 #[cfg(test)]
 mod window_manager_builder_dc_tests {
     use super::WindowManagerBuilder;
+    use crate::utils::logger::Logger;
+    use regex::Regex;
     use windows::Win32::UI::WindowsAndMessaging::{CS_CLASSDC, CS_OWNDC, CS_PARENTDC};
     #[test]
     fn test_create_unique_dc() {
-        let mut manager_builder = WindowManagerBuilder::new();
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
         manager_builder.create_unique_dc();
 
         assert_eq!(manager_builder.style, CS_OWNDC)
     }
     #[test]
     fn test_create_single_dc() {
-        let mut manager_builder = WindowManagerBuilder::new();
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
         manager_builder.create_single_dc();
 
         assert_eq!(manager_builder.style, CS_CLASSDC)
     }
     #[test]
     fn test_create_cache_dc() {
-        let mut manager_builder = WindowManagerBuilder::new();
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
         manager_builder.create_cache_dc();
 
         assert_eq!(manager_builder.style, CS_PARENTDC)
     }
     #[test]
     fn test_single_dc_is_set() {
-        let mut manager_builder = WindowManagerBuilder::new();
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
         manager_builder
             .create_cache_dc()
             .create_single_dc()
@@ -268,80 +791,103 @@ mod window_manager_builder_dc_tests {
         // Device context should be based on the first one set
         assert_eq!(manager_builder.style, CS_PARENTDC)
     }
+    #[test]
+    fn test_single_dc_is_set_warning() {
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
+        manager_builder.create_unique_dc().create_cache_dc();
+
+        let warning = Regex::new(
+            r"\[WARNING\] \d{4}-\d{1,2}-\d{1,2} \d{1,2}:\d{1,2}:\d{1,2}.\d{1,3}: WindowManagerBuilder::is_dc_set\(\) The device context has already been set to 'CS_OWNDC'\n",
+        )
+        .unwrap();
+        assert!(warning.is_match(&String::from_utf8(buffer).unwrap()))
+    }
 }
 #[cfg(test)]
 mod window_manager_builder_tests {
     use super::WindowManagerBuilder;
+    use crate::utils::logger::Logger;
     use windows::Win32::UI::WindowsAndMessaging::{
         CS_BYTEALIGNCLIENT, CS_BYTEALIGNWINDOW, CS_DBLCLKS, CS_DROPSHADOW, CS_GLOBALCLASS,
         CS_HREDRAW, CS_NOCLOSE, CS_SAVEBITS, CS_VREDRAW,
     };
     #[test]
     fn test_add_byte_align_clients() {
-        let mut manager_builder = WindowManagerBuilder::new();
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
         manager_builder.align_byte_client();
 
         assert_eq!(manager_builder.style, CS_BYTEALIGNCLIENT)
     }
     #[test]
     fn test_add_byte_align_window() {
-        let mut manager_builder = WindowManagerBuilder::new();
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
         manager_builder.align_byte_window();
 
         assert_eq!(manager_builder.style, CS_BYTEALIGNWINDOW)
     }
     #[test]
     fn test_listen_to_dbclick() {
-        let mut manager_builder = WindowManagerBuilder::new();
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
         manager_builder.listen_to_dbclick();
 
         assert_eq!(manager_builder.style, CS_DBLCLKS)
     }
     #[test]
     fn test_enable_drop_shadow() {
-        let mut manager_builder = WindowManagerBuilder::new();
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
         manager_builder.enable_drop_shadow();
 
         assert_eq!(manager_builder.style, CS_DROPSHADOW)
     }
     #[test]
     fn test_make_global() {
-        let mut manager_builder = WindowManagerBuilder::new();
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
         manager_builder.make_global();
 
         assert_eq!(manager_builder.style, CS_GLOBALCLASS)
     }
     #[test]
     fn test_listen_to_vert() {
-        let mut manager_builder = WindowManagerBuilder::new();
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
         manager_builder.listen_to_vert();
 
         assert_eq!(manager_builder.style, CS_VREDRAW)
     }
     #[test]
     fn test_listen_to_hori() {
-        let mut manager_builder = WindowManagerBuilder::new();
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
         manager_builder.listen_to_hori();
 
         assert_eq!(manager_builder.style, CS_HREDRAW)
     }
     #[test]
     fn test_disable_close() {
-        let mut manager_builder = WindowManagerBuilder::new();
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
         manager_builder.disable_close();
 
         assert_eq!(manager_builder.style, CS_NOCLOSE)
     }
     #[test]
     fn test_save_bitmap() {
-        let mut manager_builder = WindowManagerBuilder::new();
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
         manager_builder.save_bitmap();
 
         assert_eq!(manager_builder.style, CS_SAVEBITS)
     }
     #[test]
     fn test_multiple_options() {
-        let mut manager_builder = WindowManagerBuilder::new();
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
         manager_builder
             .align_byte_client()
             .disable_close()
@@ -350,20 +896,72 @@ mod window_manager_builder_tests {
 
         assert_eq!(manager_builder.style.0, 137728)
     }
+    #[test]
+    fn test_clear_style() {
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
+        manager_builder
+            .align_byte_client()
+            .listen_to_dbclick()
+            .create_unique_dc()
+            .clear_style();
+
+        assert_eq!(manager_builder.style, Default::default())
+    }
+    #[test]
+    fn test_disable_options() {
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
+        manager_builder
+            .align_byte_client()
+            .align_byte_window()
+            .listen_to_dbclick()
+            .enable_drop_shadow()
+            .make_global()
+            .listen_to_vert()
+            .listen_to_hori()
+            .disable_close()
+            .save_bitmap()
+            .disable_align_byte_client()
+            .disable_align_byte_window()
+            .disable_dbclick()
+            .disable_drop_shadow()
+            .disable_global()
+            .disable_vert()
+            .disable_hori()
+            .enable_close()
+            .disable_save_bitmap();
+
+        assert_eq!(manager_builder.style, Default::default())
+    }
+    #[test]
+    fn test_disable_keeps_other_options() {
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
+        manager_builder
+            .listen_to_vert()
+            .listen_to_hori()
+            .disable_vert();
+
+        assert_eq!(manager_builder.style, CS_HREDRAW)
+    }
 }
 #[cfg(test)]
 mod window_manager_builder_class_tests {
-    use super::WindowManagerBuilder;
+    use super::{BuildError, WindowManagerBuilder};
+    use crate::utils::logger::Logger;
     #[test]
     #[should_panic(expected = "[Error] Window Manager name can not be empty")]
     fn test_set_name_empty() {
-        let mut manager_builder = WindowManagerBuilder::new();
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
         manager_builder.set_name("").build();
     }
     #[test]
     fn test_set_name_not_exists() {
         let name = "test-name-not-exists";
-        let mut manager_builder = WindowManagerBuilder::new();
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
         manager_builder.set_name(name).build();
 
         assert!(manager_builder.classname == name)
@@ -372,14 +970,16 @@ mod window_manager_builder_class_tests {
     #[should_panic(expected = "[Error] Window Manager 'test-name-exists' already exists")]
     fn test_set_name_exists() {
         let name = "test-name-exists";
-        let mut manager_builder = WindowManagerBuilder::new();
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
         manager_builder.set_name(name).build();
         manager_builder.set_name(name).build();
     }
     #[test]
     #[should_panic(expected = "[Error] Window Manager Menu name can not be empty")]
     fn test_set_menu_empty() {
-        let mut manager_builder = WindowManagerBuilder::new();
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
         manager_builder
             .set_name("test-menu-empty")
             .set_menu("")
@@ -388,7 +988,8 @@ mod window_manager_builder_class_tests {
     #[test]
     fn test_set_menu_not_exists() {
         let name = "test";
-        let mut manager_builder = WindowManagerBuilder::new();
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
         manager_builder
             .set_name("test-menu-not-exists")
             .set_menu(name)
@@ -396,4 +997,456 @@ mod window_manager_builder_class_tests {
 
         assert!(manager_builder.menuname.unwrap() == name)
     }
+    #[test]
+    fn test_try_build_empty_name() {
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
+        let manager = manager_builder.set_name("").try_build();
+
+        assert_eq!(manager.err(), Some(BuildError::EmptyName))
+    }
+    #[test]
+    fn test_try_build_empty_menu_name() {
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
+        let manager = manager_builder
+            .set_name("test-try-build-menu-empty")
+            .set_menu("")
+            .try_build();
+
+        assert_eq!(manager.err(), Some(BuildError::EmptyMenuName))
+    }
+    #[test]
+    fn test_try_build_nul_names() {
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
+        let manager = manager_builder.set_name("test-try\0-build").try_build();
+        assert_eq!(manager.err(), Some(BuildError::InvalidName));
+
+        let manager = manager_builder
+            .set_name("test-try-build-nul-menu")
+            .set_menu("menu\0name")
+            .try_build();
+        assert_eq!(manager.err(), Some(BuildError::InvalidMenuName))
+    }
+    #[test]
+    fn test_set_background() {
+        use crate::window::win::{
+            instance::Instance,
+            paint::{Background, HatchStyle},
+            winstr::to_pcstr,
+        };
+        use windows::Win32::UI::WindowsAndMessaging::*;
+
+        let backgrounds = [
+            ("test-background-solid", Background::Solid(255, 0, 0)),
+            (
+                "test-background-hatch",
+                Background::Hatch(HatchStyle::Cross, 0, 0, 255),
+            ),
+            (
+                "test-background-checker",
+                Background::Checker {
+                    cell: 8,
+                    color_a: (255, 255, 255),
+                    color_b: (204, 204, 204),
+                },
+            ),
+        ];
+        for (name, background) in backgrounds {
+            let mut buffer = Vec::new();
+            let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
+            manager_builder.set_name(name).set_background(background);
+            assert!(manager_builder.try_build().is_ok());
+
+            let (_buffer, name) = to_pcstr(name).unwrap();
+            let mut class = WNDCLASSA::default();
+            unsafe {
+                GetClassInfoA(Instance::this(), name, &mut class).unwrap();
+                assert!(!class.hbrBackground.is_invalid());
+                _ = UnregisterClassA(name, Instance::this());
+            }
+        }
+    }
+    #[test]
+    fn test_try_build_exists() {
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
+        manager_builder.set_name("test-try-build-exists");
+        let manager1 = manager_builder.try_build();
+        let manager2 = manager_builder.try_build();
+
+        assert!(manager1.is_ok());
+        assert_eq!(manager2.err(), Some(BuildError::ClassAlreadyExists))
+    }
+    #[test]
+    fn test_try_build_register_failed() {
+        use windows::Win32::Foundation::ERROR_CLASS_ALREADY_EXISTS;
+
+        let mut buffer = Vec::new();
+        let mut manager_builder = WindowManagerBuilder::new(Logger::new(&mut buffer, 2));
+        // Longer than the 256 character class name limit
+        let name = "test-try-build-failed-".repeat(16);
+        manager_builder.set_name(&name);
+
+        assert!(matches!(
+            manager_builder.try_build().err(),
+            Some(BuildError::RegisterFailed(code)) if code != ERROR_CLASS_ALREADY_EXISTS.0
+        ))
+    }
+}
+#[cfg(test)]
+mod wndproc_tests {
+    use super::*;
+    fn create_test_window() -> HWND {
+        unsafe {
+            CreateWindowExA(
+                WINDOW_EX_STYLE::default(),
+                s!("STATIC"),
+                s!("test-window"),
+                WS_OVERLAPPEDWINDOW,
+                0,
+                0,
+                100,
+                100,
+                None,
+                None,
+                Instance::this(),
+                None,
+            )
+        }
+    }
+    #[test]
+    fn test_erase_background_double_buffered() {
+        let window = create_test_window();
+        set_double_buffered(window, true);
+
+        assert!(is_double_buffered(window));
+        assert_eq!(
+            wndproc(window, WM_ERASEBKGND, WPARAM(0), LPARAM(0)),
+            LRESULT(1)
+        );
+        unsafe { _ = DestroyWindow(window) };
+    }
+    #[test]
+    fn test_wndproc_queues_events() {
+        use crate::window::win::event::poll_event;
+
+        let window = create_test_window();
+        wndproc(window, WM_SIZE, WPARAM(0), LPARAM((600 << 16) | 800));
+        wndproc(window, WM_KEYDOWN, WPARAM(0x41), LPARAM(0x4000_0001));
+
+        assert_eq!(
+            poll_event(),
+            Some(Event::Resize {
+                width: 800,
+                height: 600
+            })
+        );
+        assert_eq!(
+            poll_event(),
+            Some(Event::KeyDown {
+                key: 0x41,
+                repeat: true
+            })
+        );
+        assert_eq!(poll_event(), None);
+        unsafe { _ = DestroyWindow(window) };
+    }
+    #[test]
+    fn test_dpi_changed() {
+        use crate::window::win::event::poll_event;
+
+        let window = create_test_window();
+        assert_eq!(window_dpi(window), DEFAULT_DPI);
+
+        let suggested = RECT {
+            left: 10,
+            top: 20,
+            right: 160,
+            bottom: 170,
+        };
+        wndproc(
+            window,
+            WM_DPICHANGED,
+            WPARAM((144 << 16) | 144),
+            LPARAM(&suggested as *const RECT as isize),
+        );
+
+        let mut rect = RECT::default();
+        unsafe { _ = GetWindowRect(window, &mut rect) };
+        assert_eq!(window_dpi(window), 144);
+        assert_eq!(rect, suggested);
+        assert_eq!(poll_event(), Some(Event::DpiChanged { dpi: 144 }));
+        unsafe { _ = DestroyWindow(window) };
+    }
+    fn is_layered(window: HWND) -> bool {
+        let style = unsafe { GetWindowLongPtrA(window, GWL_EXSTYLE) } as u32;
+        style & WS_EX_LAYERED.0 != 0
+    }
+    #[test]
+    fn test_set_opacity_toggles_layered() {
+        let window = create_test_window();
+        set_opacity(window, 128);
+        assert!(is_layered(window));
+
+        set_opacity(window, 255);
+        assert!(!is_layered(window));
+        unsafe { _ = DestroyWindow(window) };
+    }
+    #[test]
+    fn test_opaque_with_color_key_stays_layered() {
+        let window = create_test_window();
+        set_color_key(window, 255, 0, 255);
+        set_opacity(window, 128);
+        set_opacity(window, 255);
+        assert!(is_layered(window));
+
+        clear_color_key(window);
+        assert!(!is_layered(window));
+        unsafe { _ = DestroyWindow(window) };
+    }
+    #[test]
+    fn test_erase_background_not_double_buffered() {
+        use windows::Win32::Graphics::Gdi::{GetDC, ReleaseDC};
+
+        let window = create_test_window();
+        set_double_buffered(window, true);
+        set_double_buffered(window, false);
+
+        assert!(!is_double_buffered(window));
+        unsafe {
+            let dc = GetDC(window);
+            // Falls through to the default erase
+            assert_eq!(
+                wndproc(window, WM_ERASEBKGND, WPARAM(dc.0 as usize), LPARAM(0)),
+                DefWindowProcA(window, WM_ERASEBKGND, WPARAM(dc.0 as usize), LPARAM(0))
+            );
+            ReleaseDC(window, dc);
+            _ = DestroyWindow(window);
+        }
+    }
+    #[test]
+    fn test_centered_position() {
+        // Second monitor to the right of a 1920x1080 primary, minus the taskbar
+        let work_area = RECT {
+            left: 1920,
+            top: 0,
+            right: 3840,
+            bottom: 1040,
+        };
+        // 800x600 client area inside an 8 pixel border and 31 pixel title bar
+        let window = RECT {
+            left: 0,
+            top: 0,
+            right: 816,
+            bottom: 639,
+        };
+        let client = RECT {
+            left: 8,
+            top: 31,
+            right: 808,
+            bottom: 631,
+        };
+
+        assert_eq!(centered_position(&work_area, &window, &client), (2472, 189));
+    }
+    #[test]
+    fn test_center_on_active_monitor() {
+        let window = create_test_window();
+        center_on_active_monitor(window);
+
+        let mut monitor = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        let (mut rect, mut client) = (RECT::default(), RECT::default());
+        let mut origin = POINT::default();
+        unsafe {
+            _ = GetMonitorInfoA(
+                MonitorFromWindow(window, MONITOR_DEFAULTTONEAREST),
+                &mut monitor,
+            );
+            _ = GetWindowRect(window, &mut rect);
+            _ = GetClientRect(window, &mut client);
+            _ = ClientToScreen(window, &mut origin);
+        }
+        let work = monitor.rcWork;
+        // Client area is centered to within a pixel of rounding
+        assert!(
+            ((origin.x + client.right / 2) - (work.left + work.right) / 2).abs() <= 1
+                && ((origin.y + client.bottom / 2) - (work.top + work.bottom) / 2).abs() <= 1
+        );
+        unsafe { _ = DestroyWindow(window) };
+    }
+    #[test]
+    fn test_set_fullscreen_restores_style() {
+        let window = create_test_window();
+        let style = unsafe { GetWindowLongPtrA(window, GWL_STYLE) } as u32;
+        set_fullscreen(window, true);
+
+        let fullscreen = unsafe { GetWindowLongPtrA(window, GWL_STYLE) } as u32;
+        assert!(is_fullscreen(window));
+        assert_ne!(fullscreen & WS_POPUP.0, 0);
+        assert_eq!(fullscreen & WS_CAPTION.0, 0);
+        assert_eq!(fullscreen & WS_THICKFRAME.0, 0);
+
+        set_fullscreen(window, false);
+        assert!(!is_fullscreen(window));
+        assert_eq!(
+            unsafe { GetWindowLongPtrA(window, GWL_STYLE) } as u32,
+            style
+        );
+        unsafe { _ = DestroyWindow(window) };
+    }
+    #[test]
+    fn test_remove_window_props() {
+        let window = create_test_window();
+        set_double_buffered(window, true);
+        unsafe { _ = SetPropA(window, DPI, HANDLE(144)) };
+        set_fullscreen(window, true);
+        set_cursor(window, HCURSOR(1));
+        remove_window_props(window);
+
+        assert!(!is_double_buffered(window));
+        assert!(!is_fullscreen(window));
+        assert_eq!(window_dpi(window), DEFAULT_DPI);
+        assert_eq!(window_cursor(window), None);
+        unsafe { _ = DestroyWindow(window) };
+    }
+    #[test]
+    fn test_set_cursor() {
+        let window = create_test_window();
+        assert_eq!(window_cursor(window), None);
+
+        let (cross, arrow) = unsafe {
+            (
+                LoadCursorW(None, IDC_CROSS).unwrap(),
+                LoadCursorW(None, IDC_ARROW).unwrap(),
+            )
+        };
+        set_cursor(window, cross);
+        assert_eq!(window_cursor(window), Some(cross));
+        assert_eq!(
+            wndproc(
+                window,
+                WM_SETCURSOR,
+                WPARAM(window.0 as usize),
+                LPARAM(HTCLIENT as isize)
+            ),
+            LRESULT(1)
+        );
+        assert_eq!(unsafe { GetCursor() }, cross);
+
+        // Switching replaces the stored cursor
+        set_cursor(window, arrow);
+        assert_eq!(window_cursor(window), Some(arrow));
+
+        set_cursor(window, HCURSOR::default());
+        assert_eq!(window_cursor(window), None);
+        unsafe { _ = DestroyWindow(window) };
+    }
+    // Shell drop holding `paths` in the `DROPFILES` layout, freed by `DragFinish`
+    fn create_test_drop(paths: &[&str]) -> HDROP {
+        use windows::Win32::{
+            System::Memory::{GlobalAlloc, GMEM_FIXED},
+            UI::Shell::DROPFILES,
+        };
+
+        // Nul terminated paths ending with an empty one
+        let mut names: Vec<u8> = paths
+            .iter()
+            .flat_map(|path| path.bytes().chain([0]))
+            .collect();
+        names.push(0);
+        let header = std::mem::size_of::<DROPFILES>();
+        unsafe {
+            let memory = GlobalAlloc(GMEM_FIXED, header + names.len()).unwrap();
+            // A fixed allocation is its own pointer
+            let base = memory.0 as *mut u8;
+            (base as *mut DROPFILES).write(DROPFILES {
+                pFiles: header as u32,
+                ..Default::default()
+            });
+            std::ptr::copy_nonoverlapping(names.as_ptr(), base.add(header), names.len());
+            HDROP(base as isize)
+        }
+    }
+    #[test]
+    fn test_dropped_files() {
+        let paths = ["C:\\tiles\\grass.bmp", "C:\\tiles\\water.png"];
+        let drop = create_test_drop(&paths);
+
+        assert_eq!(dropped_files(drop), paths);
+        unsafe { DragFinish(drop) };
+    }
+    #[test]
+    fn test_accept_files() {
+        let window = create_test_window();
+        accept_files(window, true);
+        let style = unsafe { GetWindowLongPtrA(window, GWL_EXSTYLE) } as u32;
+        assert_ne!(style & WS_EX_ACCEPTFILES.0, 0);
+
+        accept_files(window, false);
+        let style = unsafe { GetWindowLongPtrA(window, GWL_EXSTYLE) } as u32;
+        assert_eq!(style & WS_EX_ACCEPTFILES.0, 0);
+        unsafe { _ = DestroyWindow(window) };
+    }
+    #[test]
+    fn test_wndproc_files_dropped() {
+        use crate::window::win::event::poll_event;
+
+        let window = create_test_window();
+        let drop = create_test_drop(&["C:\\tiles\\grass.bmp"]);
+        wndproc(window, WM_DROPFILES, WPARAM(drop.0 as usize), LPARAM(0));
+
+        assert_eq!(
+            poll_event(),
+            Some(Event::FilesDropped(
+                vec!["C:\\tiles\\grass.bmp".to_string()]
+            ))
+        );
+        unsafe { _ = DestroyWindow(window) };
+    }
+    #[test]
+    fn test_metadata_round_trip() {
+        unsafe {
+            let class = WNDCLASSA {
+                lpfnWndProc: Some(wndproc),
+                hInstance: Instance::this(),
+                lpszClassName: s!("test-metadata"),
+                cbClsExtra: 8,
+                cbWndExtra: 16,
+                ..Default::default()
+            };
+            assert_ne!(RegisterClassA(&class), 0);
+            let window = CreateWindowExA(
+                WINDOW_EX_STYLE::default(),
+                s!("test-metadata"),
+                s!("test-window"),
+                WS_OVERLAPPEDWINDOW,
+                0,
+                0,
+                100,
+                100,
+                None,
+                None,
+                Instance::this(),
+                None,
+            );
+
+            assert!(set_window_metadata(window, 8, 42));
+            assert_eq!(window_metadata(window, 8), Some(42));
+            assert!(set_class_metadata(window, 0, -7));
+            assert_eq!(class_metadata(window, 0), Some(-7));
+            // Out of the allocated bytes
+            assert!(!set_window_metadata(window, 12, 42));
+            assert_eq!(window_metadata(window, 16), None);
+            assert!(!set_class_metadata(window, -8, 42));
+            assert_eq!(class_metadata(window, 8), None);
+
+            _ = DestroyWindow(window);
+            _ = UnregisterClassA(s!("test-metadata"), Instance::this());
+        }
+    }
 }
@@ -0,0 +1,70 @@
+//! Owned nul terminated strings for passing `&str` to the system
+use std::ffi::CString;
+use windows::core::{PCSTR, PCWSTR};
+
+/// Copy a string into a nul terminated ANSI buffer
+///
+/// A single trailing `\0` is accepted and not duplicated
+///
+/// The pointer is only valid while the returned buffer is alive
+///
+/// `None` if the string contains an interior `\0`
+pub(crate) fn to_pcstr(s: &str) -> Option<(CString, PCSTR)> {
+    let buffer = CString::new(s.strip_suffix('\0').unwrap_or(s)).ok()?;
+    let pointer = PCSTR(buffer.as_ptr() as *const u8);
+    Some((buffer, pointer))
+}
+
+/// Copy a string into a nul terminated UTF-16 buffer
+///
+/// A single trailing `\0` is accepted and not duplicated
+///
+/// The pointer is only valid while the returned buffer is alive
+///
+/// `None` if the string contains an interior `\0`
+pub(crate) fn to_pcwstr(s: &str) -> Option<(Vec<u16>, PCWSTR)> {
+    let s = s.strip_suffix('\0').unwrap_or(s);
+    if s.contains('\0') {
+        return None;
+    }
+    let buffer: Vec<u16> = s.encode_utf16().chain(Some(0)).collect();
+    let pointer = PCWSTR(buffer.as_ptr());
+    Some((buffer, pointer))
+}
+
+#[cfg(test)]
+mod winstr_tests {
+    use super::*;
+
+    #[test]
+    fn test_to_pcstr_terminated() {
+        let (buffer, pointer) = to_pcstr("test.bmp").unwrap();
+        assert_eq!(buffer.as_bytes_with_nul(), b"test.bmp\0");
+        assert_eq!(unsafe { pointer.as_bytes() }, b"test.bmp");
+    }
+    #[test]
+    fn test_to_pcstr_trailing_nul() {
+        let (buffer, _) = to_pcstr("test.bmp\0").unwrap();
+        assert_eq!(buffer.as_bytes_with_nul(), b"test.bmp\0");
+    }
+    #[test]
+    fn test_to_pcstr_interior_nul() {
+        assert!(to_pcstr("test\0.bmp").is_none());
+        assert!(to_pcstr("test.bmp\0\0").is_none());
+    }
+    #[test]
+    fn test_to_pcwstr_terminated() {
+        let (buffer, pointer) = to_pcwstr("tést").unwrap();
+        assert_eq!(buffer, vec![0x74, 0xE9, 0x73, 0x74, 0]);
+        assert_eq!(unsafe { pointer.to_string() }.unwrap(), "tést");
+    }
+    #[test]
+    fn test_to_pcwstr_trailing_nul() {
+        let (buffer, _) = to_pcwstr("test\0").unwrap();
+        assert_eq!(buffer, vec![0x74, 0x65, 0x73, 0x74, 0]);
+    }
+    #[test]
+    fn test_to_pcwstr_interior_nul() {
+        assert!(to_pcwstr("te\0st").is_none());
+    }
+}